@@ -1,11 +1,16 @@
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
 use std::cmp::Ordering;
-use chrono::{DateTime, Local, NaiveDate, Datelike};
-use clap::{Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, Datelike, Months, TimeZone, Weekday};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use fs2::FileExt;
+use regex::{Regex, RegexBuilder};
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum Language {
     Chinese, 
@@ -82,6 +87,14 @@ impl Translations {
         }.to_string()
     }
 
+    fn uid(&self) -> String {
+        match self.lang {
+            Language::English => "UID",
+            Language::Japanese => "固定ID",
+            Language::Chinese => "固定ID",
+        }.to_string()
+    }
+
     fn description(&self) -> String {
         match self.lang {
             Language::English => "Description",
@@ -162,6 +175,22 @@ impl Translations {
         }
     }
 
+    fn ambiguous_id(&self) -> &'static str {
+        match self.lang {
+            Language::English => "The UID prefix matches more than one task, please enter more characters",
+            Language::Japanese => "UIDの前方一致が複数のタスクに該当します。もっと長く入力してください",
+            Language::Chinese => "固定ID前缀匹配到多个任务，请输入更多字符",
+        }
+    }
+
+    fn nested_subtask(&self) -> &'static str {
+        match self.lang {
+            Language::English => "Subtasks cannot have subtasks of their own",
+            Language::Japanese => "サブタスクにはサブタスクを追加できません",
+            Language::Chinese => "子任务不能再添加子任务",
+        }
+    }
+
     fn task_already_completed(&self) -> &'static str {
         match self.lang {
             Language::English => "Task does not exist or is already completed",
@@ -186,11 +215,43 @@ impl Translations {
         }
     }
 
+    fn list_changed(&self) -> &'static str {
+        match self.lang {
+            Language::English => "The task list was changed by another jodo process; check it with 'jodo -l' and try again",
+            Language::Japanese => "タスクリストが別のjodoプロセスによって変更されました。'jodo -l'で確認してから再試行してください",
+            Language::Chinese => "任务列表已被其他jodo进程修改，请使用 'jodo -l' 查看后重试",
+        }
+    }
+
+    fn nothing_to_undo(&self) -> &'static str {
+        match self.lang {
+            Language::English => "Nothing to undo",
+            Language::Japanese => "元に戻す操作はありません",
+            Language::Chinese => "没有可以撤销的操作",
+        }
+    }
+
+    fn nothing_to_redo(&self) -> &'static str {
+        match self.lang {
+            Language::English => "Nothing to redo",
+            Language::Japanese => "やり直す操作はありません",
+            Language::Chinese => "没有可以重做的操作",
+        }
+    }
+
+    fn invalid_duration(&self) -> &'static str {
+        match self.lang {
+            Language::English => "Invalid duration, please use a number followed by h, d or w, e.g. 30d",
+            Language::Japanese => "期間の形式が無効です。数字の後にh、d、wを付けてください。例: 30d",
+            Language::Chinese => "时长格式错误，请使用数字加 h、d 或 w，例如 30d",
+        }
+    }
+
     fn invalid_date_format(&self) -> &'static str {
         match self.lang {
-            Language::English => "Invalid date format, please use YYYY-MM-DD format",
-            Language::Japanese => "日付の形式が無効です。YYYY-MM-DD形式を使用してください",
-            Language::Chinese => "日期格式错误，请使用 YYYY-MM-DD 格式",
+            Language::English => "Invalid date format, please use YYYY-MM-DD or YYYY-MM-DD HH:MM format",
+            Language::Japanese => "日付の形式が無効です。YYYY-MM-DD または YYYY-MM-DD HH:MM 形式を使用してください",
+            Language::Chinese => "日期格式错误，请使用 YYYY-MM-DD 或 YYYY-MM-DD HH:MM 格式",
         }
     }
 
@@ -242,30 +303,118 @@ impl Translations {
         }
     }
 
-    fn starred(&self) -> String {
+    fn stats_title(&self) -> String {
+        match self.lang {
+            Language::English => "Statistics:",
+            Language::Japanese => "統計:",
+            Language::Chinese => "统计:",
+        }.to_string()
+    }
+
+    fn stats_open(&self) -> String {
         match self.lang {
-            Language::English => "Starred",
-            Language::Japanese => "重要マーク",
-            Language::Chinese => "重要标记",
+            Language::English => "Open",
+            Language::Japanese => "未完了",
+            Language::Chinese => "未完成",
         }.to_string()
     }
 
-    fn yes(&self) -> String {
+    fn stats_completed(&self) -> String {
         match self.lang {
-            Language::English => "Yes ★".yellow().to_string(),
-            Language::Japanese => "はい ★".yellow().to_string(),
-            Language::Chinese => "是 ★".yellow().to_string(),
+            Language::English => "Completed",
+            Language::Japanese => "完了",
+            Language::Chinese => "已完成",
+        }.to_string()
+    }
+
+    fn stats_overdue(&self) -> String {
+        match self.lang {
+            Language::English => "Overdue",
+            Language::Japanese => "期限切れ",
+            Language::Chinese => "已过期",
+        }.to_string()
+    }
+
+    fn completed_per_day(&self, days: u32) -> String {
+        match self.lang {
+            Language::English => format!("Completed per day (last {} days)", days),
+            Language::Japanese => format!("日ごとの完了数（過去{}日）", days),
+            Language::Chinese => format!("每天完成的任务（最近{}天）", days),
+        }
+    }
+
+    fn completed_per_week(&self, weeks: u32) -> String {
+        match self.lang {
+            Language::English => format!("Completed per week (last {} weeks)", weeks),
+            Language::Japanese => format!("週ごとの完了数（過去{}週）", weeks),
+            Language::Chinese => format!("每周完成的任务（最近{}周）", weeks),
         }
     }
 
-    fn no(&self) -> String {
+    fn average_lead_time(&self) -> String {
+        match self.lang {
+            Language::English => "Avg lead time",
+            Language::Japanese => "平均所要時間",
+            Language::Chinese => "平均用时",
+        }.to_string()
+    }
+
+    fn on_time_rate(&self) -> String {
+        match self.lang {
+            Language::English => "On time",
+            Language::Japanese => "期限内完了率",
+            Language::Chinese => "按时完成率",
+        }.to_string()
+    }
+
+    fn by_tag(&self) -> String {
+        match self.lang {
+            Language::English => "By tag",
+            Language::Japanese => "タグ別",
+            Language::Chinese => "按标签",
+        }.to_string()
+    }
+
+    // 时长，如 "2 days 3 hours"，不足一小时时显示分钟
+    fn duration(&self, duration: chrono::Duration) -> String {
+        let (days, hours, minutes) = (duration.num_days(), duration.num_hours() % 24, duration.num_minutes() % 60);
         match self.lang {
-            Language::English => "No".normal().to_string(),
-            Language::Japanese => "いいえ".normal().to_string(),
-            Language::Chinese => "否".normal().to_string(),
+            Language::English if days > 0 => format!("{} days {} hours", days, hours),
+            Language::English if hours > 0 => format!("{} hours {} minutes", hours, minutes),
+            Language::English => format!("{} minutes", minutes),
+            Language::Japanese if days > 0 => format!("{}日{}時間", days, hours),
+            Language::Japanese if hours > 0 => format!("{}時間{}分", hours, minutes),
+            Language::Japanese => format!("{}分", minutes),
+            Language::Chinese if days > 0 => format!("{}天{}小时", days, hours),
+            Language::Chinese if hours > 0 => format!("{}小时{}分钟", hours, minutes),
+            Language::Chinese => format!("{}分钟", minutes),
         }
     }
 
+    fn stats_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show statistics (--days 30 or --weeks 8)",
+            Language::Japanese => "統計を表示（--days 30 または --weeks 8）",
+            Language::Chinese => "显示统计信息（--days 30 或 --weeks 8）",
+        }.to_string()
+    }
+
+    fn completed_at(&self) -> String {
+        match self.lang {
+            Language::English => "Completed",
+            Language::Japanese => "完了日時",
+            Language::Chinese => "完成时间",
+        }.to_string()
+    }
+
+    fn completed_since_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Tasks completed since a date, most recent last",
+            Language::Japanese => "指定日以降に完了したタスク（完了順）",
+            Language::Chinese => "指定日期之后完成的任务（按完成时间排列）",
+        }.to_string()
+    }
+
     fn created_at(&self) -> String {
         match self.lang {
             Language::English => "Created at",
@@ -314,6 +463,14 @@ impl Translations {
         }.to_string()
     }
 
+    fn note_uid(&self) -> String {
+        match self.lang {
+            Language::English => "Note: Tasks can also be addressed by their UID or its first 8 characters (see --show); use '@' to force a UID prefix, e.g. '@1234abcd'",
+            Language::Japanese => "注意: タスクは固定ID（またはその先頭8文字）でも指定できます（--showで確認）。'@'を付けるとUIDの前方一致として扱います。例: '@1234abcd'",
+            Language::Chinese => "注意: 也可以使用任务的固定ID（或其前8位）来指定任务（通过 --show 查看），以'@'开头可强制按固定ID前缀匹配，例如 '@1234abcd'",
+        }.to_string()
+    }
+
     fn other_options(&self) -> String {
         match self.lang {
             Language::English => "Other Options:",
@@ -362,6 +519,54 @@ impl Translations {
         }
     }
 
+    fn file_corrupt(&self, path: &str, err: &str, backup: &str) -> String {
+        match self.lang {
+            Language::English => format!("Cannot parse {} ({}); a copy was kept at {} and the file was left untouched", path, err, backup),
+            Language::Japanese => format!("{} を解析できません ({})。コピーを {} に保存し、元のファイルは変更していません", path, err, backup),
+            Language::Chinese => format!("无法解析 {} ({})，已备份到 {}，原文件未被改动", path, err, backup),
+        }
+    }
+
+    fn journal_corrupt(&self, path: &str, err: &str, backup: &str) -> String {
+        match self.lang {
+            Language::English => format!("Cannot parse {} ({}); a copy was kept at {} and the undo history starts over", path, err, backup),
+            Language::Japanese => format!("{} を解析できません ({})。コピーを {} に保存し、操作履歴を新しく始めます", path, err, backup),
+            Language::Chinese => format!("无法解析 {} ({})，已备份到 {}，操作日志将重新开始记录", path, err, backup),
+        }
+    }
+
+    fn config_not_saved(&self, path: &str) -> String {
+        match self.lang {
+            Language::English => format!("Settings were not saved because {} cannot be parsed; fix or remove it first", path),
+            Language::Japanese => format!("{} を解析できないため、設定は保存されませんでした。修正または削除してください", path),
+            Language::Chinese => format!("无法解析 {}，设置未保存；请先修复或删除该文件", path),
+        }
+    }
+
+    fn task_lists(&self) -> String {
+        match self.lang {
+            Language::English => "Task Lists:",
+            Language::Japanese => "タスクリスト:",
+            Language::Chinese => "任务列表:",
+        }.to_string()
+    }
+
+    fn list_switched(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("Switched to task list \"{}\"", name),
+            Language::Japanese => format!("タスクリスト \"{}\" に切り替えました", name),
+            Language::Chinese => format!("已切换到任务列表 \"{}\"", name),
+        }
+    }
+
+    fn invalid_list_name(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid list name \"{}\", only letters, digits, '-' and '_' are allowed", name),
+            Language::Japanese => format!("無効なリスト名 \"{}\"。使用できるのは英数字、'-'、'_'のみです", name),
+            Language::Chinese => format!("无效的列表名 \"{}\"，只能包含字母、数字、'-'和'_'", name),
+        }
+    }
+
     fn due_status_legend(&self) -> String {
         match self.lang {
             Language::English => "Due Date Colors",
@@ -380,9 +585,9 @@ impl Translations {
     
     fn urgent(&self) -> String {
         match self.lang {
-            Language::English => "Urgent (Today)",
-            Language::Japanese => "緊急 (今日)",
-            Language::Chinese => "紧急 (今天)",
+            Language::English => "Urgent (Today or within 24 hours)",
+            Language::Japanese => "緊急 (今日または24時間以内)",
+            Language::Chinese => "紧急 (今天或24小时内)",
         }.to_string()
     }
     
@@ -525,6 +730,38 @@ impl Translations {
         }.to_string()
     }
 
+    fn list_lists_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "List all task lists",
+            Language::Japanese => "すべてのタスクリストを表示",
+            Language::Chinese => "列出所有任务列表",
+        }.to_string()
+    }
+
+    fn switch_list_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Switch the default task list",
+            Language::Japanese => "デフォルトのタスクリストを切り替え",
+            Language::Chinese => "切换默认任务列表",
+        }.to_string()
+    }
+
+    fn use_list_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Use another task list for this command",
+            Language::Japanese => "このコマンドで別のタスクリストを使用",
+            Language::Chinese => "本次命令使用指定的任务列表",
+        }.to_string()
+    }
+
+    fn data_dir_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Use another data directory (or set JODO_HOME)",
+            Language::Japanese => "別のデータディレクトリを使用（JODO_HOMEでも指定可）",
+            Language::Chinese => "使用指定的数据目录（也可设置 JODO_HOME）",
+        }.to_string()
+    }
+
     fn show_help_info(&self) -> String {
         match self.lang {
             Language::English => "Show help information",
@@ -556,11 +793,8 @@ impl Translations {
         }
     }
 
-    fn tasks_deleted(&self, ids: &[usize]) -> String {
-        let id_list = ids.iter()
-            .map(|id| id.to_string())
-            .collect::<Vec<_>>()
-            .join(", ");
+    fn tasks_deleted(&self, ids: &[String]) -> String {
+        let id_list = ids.join(", ");
             
         match self.lang {
             Language::English => format!("Tasks {} deleted", id_list),
@@ -591,247 +825,3026 @@ impl Translations {
         }
     }
 
-    fn tasks_uncompleted(&self, ids: &[String]) -> String {
-        let id_list = ids.join(", ");
+    fn operation_name(&self, operation: Operation) -> String {
+        match (self.lang, operation) {
+            (Language::English, Operation::Add) => "Add",
+            (Language::English, Operation::Edit) => "Edit",
+            (Language::English, Operation::Complete) => "Complete",
+            (Language::English, Operation::Reopen) => "Reopen",
+            (Language::English, Operation::Star) => "Star",
+            (Language::English, Operation::Unstar) => "Unstar",
+            (Language::English, Operation::Delete) => "Delete",
+            (Language::English, Operation::Restore) => "Restore",
+            (Language::English, Operation::Purge) => "Purge",
+            (Language::English, Operation::Tag) => "Tag",
+            (Language::English, Operation::Block) => "Block",
+            (Language::English, Operation::Unblock) => "Unblock",
+            (Language::English, Operation::Note) => "Note",
+            (Language::English, Operation::Archive) => "Archive",
+            (Language::Japanese, Operation::Add) => "追加",
+            (Language::Japanese, Operation::Edit) => "編集",
+            (Language::Japanese, Operation::Complete) => "完了",
+            (Language::Japanese, Operation::Reopen) => "未完了に戻す",
+            (Language::Japanese, Operation::Star) => "重要マーク",
+            (Language::Japanese, Operation::Unstar) => "重要マーク解除",
+            (Language::Japanese, Operation::Delete) => "削除",
+            (Language::Japanese, Operation::Restore) => "復元",
+            (Language::Japanese, Operation::Purge) => "完全削除",
+            (Language::Japanese, Operation::Tag) => "タグ",
+            (Language::Japanese, Operation::Block) => "依存追加",
+            (Language::Japanese, Operation::Unblock) => "依存解除",
+            (Language::Japanese, Operation::Note) => "メモ",
+            (Language::Japanese, Operation::Archive) => "アーカイブ",
+            (Language::Chinese, Operation::Add) => "添加",
+            (Language::Chinese, Operation::Edit) => "编辑",
+            (Language::Chinese, Operation::Complete) => "完成",
+            (Language::Chinese, Operation::Reopen) => "取消完成",
+            (Language::Chinese, Operation::Star) => "标记重要",
+            (Language::Chinese, Operation::Unstar) => "取消重要",
+            (Language::Chinese, Operation::Delete) => "删除",
+            (Language::Chinese, Operation::Restore) => "恢复",
+            (Language::Chinese, Operation::Purge) => "彻底删除",
+            (Language::Chinese, Operation::Tag) => "标签",
+            (Language::Chinese, Operation::Block) => "添加依赖",
+            (Language::Chinese, Operation::Unblock) => "移除依赖",
+            (Language::Chinese, Operation::Note) => "备注",
+            (Language::Chinese, Operation::Archive) => "归档",
+        }.to_string()
+    }
+
+    fn operation_undone(&self, operation: &str) -> String {
         match self.lang {
-            Language::English => format!("Tasks {} marked as incomplete", id_list),
-            Language::Japanese => format!("タスク {} が未完了としてマークされました", id_list),
-            Language::Chinese => format!("任务 {} 已标记为未完成", id_list),
+            Language::English => format!("Undid operation: {}", operation),
+            Language::Japanese => format!("操作を元に戻しました: {}", operation),
+            Language::Chinese => format!("已撤销操作: {}", operation),
         }
     }
-}
-#[derive(Debug, Serialize, Deserialize)]
-struct Config {
-    language: Language,
-}
 
-impl Config {
-    fn new() -> Self {
-        Self {
-            language: Language::Chinese,
+    fn operation_redone(&self, operation: &str) -> String {
+        match self.lang {
+            Language::English => format!("Redid operation: {}", operation),
+            Language::Japanese => format!("操作をやり直しました: {}", operation),
+            Language::Chinese => format!("已重做操作: {}", operation),
         }
     }
-    
-    fn load() -> Self {
-        let config_path = get_config_path();
-        
-        if config_path.exists() {
-            match fs::read_to_string(&config_path) {
-                Ok(contents) => {
-                    match serde_json::from_str(&contents) {
-                        Ok(config) => return config,
-                        Err(_) => return Config::new(),
-                    }
-                },
-                Err(_) => return Config::new(),
-            }
-        }
-        
-        Config::new()
+
+    fn operation_log(&self) -> String {
+        match self.lang {
+            Language::English => "Operation Log:",
+            Language::Japanese => "操作履歴:",
+            Language::Chinese => "操作日志:",
+        }.to_string()
     }
-    
-    fn save(&self) -> Result<(), io::Error> {
-        let config_path = get_config_path();
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        
-        let contents = serde_json::to_string_pretty(self)?;
-        let mut file = File::create(&config_path)?;
-        file.write_all(contents.as_bytes())?;
-        Ok(())
+
+    fn operation(&self) -> String {
+        match self.lang {
+            Language::English => "Operation",
+            Language::Japanese => "操作",
+            Language::Chinese => "操作",
+        }.to_string()
     }
-}
 
-// 获取配置文件路径
-fn get_config_path() -> PathBuf {
-    let mut path = dirs::home_dir().unwrap_or_default();
-    path.push(".jodo");
-    path.push("config.json");
-    path
-}
+    fn time(&self) -> String {
+        match self.lang {
+            Language::English => "Time",
+            Language::Japanese => "日時",
+            Language::Chinese => "时间",
+        }.to_string()
+    }
 
-// 全局语言设置
-static mut CURRENT_LANGUAGE: Language = Language::Chinese;
+    fn undone_marker(&self) -> String {
+        match self.lang {
+            Language::English => "(undone)",
+            Language::Japanese => "(取り消し済み)",
+            Language::Chinese => "(已撤销)",
+        }.to_string()
+    }
 
-fn get_translations() -> Translations {
-    unsafe {
-        Translations::new(CURRENT_LANGUAGE)
+    fn undo_last_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Undo the last change (repeatable)",
+            Language::Japanese => "直前の変更を元に戻す（繰り返し可能）",
+            Language::Chinese => "撤销最近一次修改（可多次执行）",
+        }.to_string()
     }
-}
-fn build_cli() -> Cli {
-    Cli {
-        task: None,
-        due_date: None,
-        language: None,
-        help: false,
-        version: false,
-        edit_id: None,
-        edit_content_arg: None,
-        edit_content: None,
-        complete_ids: Vec::new(),
-        undo_id: None,
-        star_id: None,
-        unstar_id: None,
-        delete_ids: Vec::new(),
-        list: false,
-        show_id: None,
-        multi_mode: false,
-        command: None,
+
+    fn redo_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Redo the last undone change",
+            Language::Japanese => "元に戻した変更をやり直す",
+            Language::Chinese => "重做最近一次撤销的修改",
+        }.to_string()
     }
-}
 
-#[derive(Parser)]
-#[clap(name = "jodo")]
-#[clap(about = "", long_about = None)]
-#[clap(version)]
-struct Cli {
-    /// 任务内容 (直接添加任务)
-    #[clap(value_name = "CONTENT")]
-    task: Option<String>,
+    fn log_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show operation history",
+            Language::Japanese => "操作履歴を表示",
+            Language::Chinese => "查看操作日志",
+        }.to_string()
+    }
 
-    /// 设置任务截止日期 (格式: YYYY-MM-DD)
-    #[clap(short = 't', long = "time")]
-    due_date: Option<String>,
+    fn tags(&self) -> String {
+        match self.lang {
+            Language::English => "Tags",
+            Language::Japanese => "タグ",
+            Language::Chinese => "标签",
+        }.to_string()
+    }
 
-    /// 设置语言 (zh-cn: 中文, en: 英文, ja: 日语)
-    #[clap(short = 'L', long = "language")]
-    language: Option<String>,
+    fn tags_updated(&self, id: &str) -> String {
+        match self.lang {
+            Language::English => format!("Tags of task {} have been updated", id),
+            Language::Japanese => format!("タスク {} のタグが更新されました", id),
+            Language::Chinese => format!("已更新任务 {} 的标签", id),
+        }
+    }
 
-    /// 显示详细的帮助信息
-    #[clap(short = 'h', long = "help", conflicts_with = "task")]
-    help: bool,
+    fn dependency_added(&self, id: &str, on: &str) -> String {
+        match self.lang {
+            Language::English => format!("Task {} is now blocked by {}", id, on),
+            Language::Japanese => format!("タスク {} は {} の完了待ちになりました", id, on),
+            Language::Chinese => format!("任务 {} 现在依赖于 {}", id, on),
+        }
+    }
 
-    /// 显示版本信息
-    #[clap(short = 'v', long = "version")]
-    version: bool,
+    fn dependency_removed(&self, id: &str, on: &str) -> String {
+        match self.lang {
+            Language::English => format!("Task {} no longer depends on {}", id, on),
+            Language::Japanese => format!("タスク {} の {} への依存を解除しました", id, on),
+            Language::Chinese => format!("任务 {} 不再依赖于 {}", id, on),
+        }
+    }
 
-    /// 编辑任务内容
-    #[clap(short = 'e', long = "edit", value_name = "ID")]
-    edit_id: Option<String>,
+    fn dependency_cycle(&self, id: &str, on: &str) -> String {
+        match self.lang {
+            Language::English => format!("Task {} cannot depend on {}: that would create a dependency cycle", id, on),
+            Language::Japanese => format!("タスク {} を {} に依存させると循環依存になります", id, on),
+            Language::Chinese => format!("任务 {} 不能依赖于 {}：会形成循环依赖", id, on),
+        }
+    }
 
-    /// 编辑任务时的新内容 (可以直接作为命令行参数)
-    #[clap(value_name = "CONTENT")]
-    edit_content_arg: Option<String>,
+    fn open_dependencies_warning(&self, id: &str, open: &str) -> String {
+        match self.lang {
+            Language::English => format!("Warning: task {} depends on unfinished tasks: {}", id, open),
+            Language::Japanese => format!("警告: タスク {} は未完了のタスクに依存しています: {}", id, open),
+            Language::Chinese => format!("警告：任务 {} 依赖的任务尚未完成：{}", id, open),
+        }
+    }
 
-    /// 编辑任务时的新内容 (通过--content参数)
+    fn depends_on(&self) -> String {
+        match self.lang {
+            Language::English => "Depends on",
+            Language::Japanese => "依存先",
+            Language::Chinese => "依赖于",
+        }.to_string()
+    }
+
+    fn block_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Task 5 cannot start until task 3 is done",
+            Language::Japanese => "タスク3が完了するまでタスク5を保留",
+            Language::Chinese => "任务3完成之前任务5无法开始",
+        }.to_string()
+    }
+
+    fn unblock_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Remove a dependency",
+            Language::Japanese => "依存関係を解除",
+            Language::Chinese => "移除依赖关系",
+        }.to_string()
+    }
+
+    fn edit_document_header(&self) -> String {
+        match self.lang {
+            Language::English => "# Edit the fields below, then save and close the editor.\n\
+                # Dates accept the same expressions as -t (see `jodo parse-date`); leave due or repeat empty to clear them.\n\
+                # Notes go on indented lines after \"notes:\". Delete everything to cancel.\n",
+            Language::Japanese => "# 以下の項目を編集し、保存してエディタを閉じてください。\n\
+                # 日付は -t と同じ表現が使えます（`jodo parse-date` を参照）。due や repeat を空にすると削除されます。\n\
+                # メモは \"notes:\" の後にインデントして書きます。すべて削除するとキャンセルします。\n",
+            Language::Chinese => "# 编辑下面的字段，然后保存并关闭编辑器。\n\
+                # 日期支持与 -t 相同的写法（见 `jodo parse-date`）；due 或 repeat 留空表示清除。\n\
+                # 备注写在 \"notes:\" 之后的缩进行中。删除全部内容即可取消。\n",
+        }.to_string()
+    }
+
+    fn document_error(&self, line: usize, message: &str) -> String {
+        match self.lang {
+            Language::English => format!("line {}: {}", line, message),
+            Language::Japanese => format!("{}行目: {}", line, message),
+            Language::Chinese => format!("第{}行: {}", line, message),
+        }
+    }
+
+    fn unknown_task_section(&self, header: &str) -> String {
+        match self.lang {
+            Language::English => format!("unknown task [{}], do not change the task headers", header),
+            Language::Japanese => format!("不明なタスク [{}]。タスクの見出しは変更しないでください", header),
+            Language::Chinese => format!("未知的任务 [{}]，请不要修改任务标题行", header),
+        }
+    }
+
+    fn duplicate_task_section(&self, header: &str) -> String {
+        match self.lang {
+            Language::English => format!("task [{}] appears more than once", header),
+            Language::Japanese => format!("タスク [{}] が複数回あります", header),
+            Language::Chinese => format!("任务 [{}] 出现了多次", header),
+        }
+    }
+
+    fn invalid_document_line(&self, line: &str) -> String {
+        match self.lang {
+            Language::English => format!("expected \"field: value\", found \"{}\"", line),
+            Language::Japanese => format!("\"項目: 値\" の形式ではありません: \"{}\"", line),
+            Language::Chinese => format!("应为 \"字段: 值\" 的格式，实际为 \"{}\"", line),
+        }
+    }
+
+    fn unknown_field(&self, field: &str) -> String {
+        match self.lang {
+            Language::English => format!("unknown field \"{}\", use {}", field, DOCUMENT_FIELDS.join(", ")),
+            Language::Japanese => format!("不明な項目 \"{}\"。{} を使用してください", field, DOCUMENT_FIELDS.join("、")),
+            Language::Chinese => format!("未知的字段 \"{}\"，请使用 {}", field, DOCUMENT_FIELDS.join("、")),
+        }
+    }
+
+    fn field_outside_task(&self, field: &str) -> String {
+        match self.lang {
+            Language::English => format!("\"{}\" must follow a task header", field),
+            Language::Japanese => format!("\"{}\" はタスクの見出しの後に書いてください", field),
+            Language::Chinese => format!("\"{}\" 必须写在任务标题行之后", field),
+        }
+    }
+
+    fn empty_description(&self) -> &'static str {
+        match self.lang {
+            Language::English => "description cannot be empty",
+            Language::Japanese => "内容を空にすることはできません",
+            Language::Chinese => "任务内容不能为空",
+        }
+    }
+
+    fn invalid_completed(&self, value: &str) -> String {
+        match self.lang {
+            Language::English => format!("invalid value \"{}\", use yes or no", value),
+            Language::Japanese => format!("無効な値 \"{}\"。yes または no を使用してください", value),
+            Language::Chinese => format!("无效的值 \"{}\"，请使用 yes 或 no", value),
+        }
+    }
+
+    fn edit_cancelled(&self) -> String {
+        match self.lang {
+            Language::English => "Edit cancelled, nothing was changed",
+            Language::Japanese => "編集をキャンセルしました。変更はありません",
+            Language::Chinese => "已取消编辑，没有任何修改",
+        }.to_string()
+    }
+
+    fn no_changes(&self) -> String {
+        match self.lang {
+            Language::English => "No changes",
+            Language::Japanese => "変更はありません",
+            Language::Chinese => "没有修改",
+        }.to_string()
+    }
+
+    fn tasks_edited(&self, count: usize) -> String {
+        match self.lang {
+            Language::English => format!("Updated {} task(s)", count),
+            Language::Japanese => format!("{} 件のタスクを更新しました", count),
+            Language::Chinese => format!("已更新 {} 个任务", count),
+        }
+    }
+
+    fn edit_interactive_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Edit all fields of a task in $EDITOR",
+            Language::Japanese => "$EDITOR でタスクの全項目を編集",
+            Language::Chinese => "在 $EDITOR 中编辑任务的所有字段",
+        }.to_string()
+    }
+
+    fn edit_all_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Edit all matching tasks in $EDITOR",
+            Language::Japanese => "条件に合うすべてのタスクを $EDITOR で編集",
+            Language::Chinese => "在 $EDITOR 中编辑所有符合条件的任务",
+        }.to_string()
+    }
+
+    fn notes(&self) -> String {
+        match self.lang {
+            Language::English => "Notes",
+            Language::Japanese => "メモ",
+            Language::Chinese => "备注",
+        }.to_string()
+    }
+
+    fn notes_updated(&self, id: &str) -> String {
+        match self.lang {
+            Language::English => format!("Notes of task {} have been updated", id),
+            Language::Japanese => format!("タスク {} のメモが更新されました", id),
+            Language::Chinese => format!("已更新任务 {} 的备注", id),
+        }
+    }
+
+    fn notes_unchanged(&self, id: &str) -> String {
+        match self.lang {
+            Language::English => format!("Notes of task {} are unchanged", id),
+            Language::Japanese => format!("タスク {} のメモは変更されていません", id),
+            Language::Chinese => format!("任务 {} 的备注没有变化", id),
+        }
+    }
+
+    fn editor_exited(&self, editor: &str) -> String {
+        match self.lang {
+            Language::English => format!("Editor \"{}\" exited with an error, nothing was saved", editor),
+            Language::Japanese => format!("エディタ \"{}\" がエラーで終了したため、保存しませんでした", editor),
+            Language::Chinese => format!("编辑器 \"{}\" 异常退出，未保存任何修改", editor),
+        }
+    }
+
+    fn note_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Edit the notes of a task in $EDITOR",
+            Language::Japanese => "$EDITOR でタスクのメモを編集",
+            Language::Chinese => "在 $EDITOR 中编辑任务备注",
+        }.to_string()
+    }
+
+    fn note_append_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Append a line to the notes",
+            Language::Japanese => "メモに1行追加",
+            Language::Chinese => "在备注末尾追加一行",
+        }.to_string()
+    }
+
+    fn invalid_tag(&self, tag: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid tag \"{}\", use +tag to add or -tag to remove", tag),
+            Language::Japanese => format!("無効なタグ \"{}\"。追加は+tag、削除は-tagを使用してください", tag),
+            Language::Chinese => format!("无效的标签 \"{}\"，使用 +tag 添加或 -tag 移除", tag),
+        }
+    }
+
+    fn tag_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Add (+) or remove (-) task tags",
+            Language::Japanese => "タスクのタグを追加(+)または削除(-)",
+            Language::Chinese => "添加(+)或移除(-)任务标签",
+        }.to_string()
+    }
+
+    fn filter_by_tag_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "List tasks with/without tags",
+            Language::Japanese => "タグでタスクを絞り込み表示",
+            Language::Chinese => "按标签筛选任务",
+        }.to_string()
+    }
+
+    fn priority(&self) -> String {
+        match self.lang {
+            Language::English => "Priority",
+            Language::Japanese => "優先度",
+            Language::Chinese => "优先级",
+        }.to_string()
+    }
+
+    fn priority_name(&self, priority: Priority) -> String {
+        match (self.lang, priority) {
+            (Language::English, Priority::High) => "High",
+            (Language::English, Priority::Medium) => "Medium",
+            (Language::English, Priority::Low) => "Low",
+            (Language::English, Priority::None) => "None",
+            (Language::Japanese, Priority::High) => "高",
+            (Language::Japanese, Priority::Medium) => "中",
+            (Language::Japanese, Priority::Low) => "低",
+            (Language::Japanese, Priority::None) => "なし",
+            (Language::Chinese, Priority::High) => "高",
+            (Language::Chinese, Priority::Medium) => "中",
+            (Language::Chinese, Priority::Low) => "低",
+            (Language::Chinese, Priority::None) => "无",
+        }.to_string()
+    }
+
+    fn priority_updated(&self, id: &str, priority: &str) -> String {
+        match self.lang {
+            Language::English => format!("Priority of task {} set to {}", id, priority),
+            Language::Japanese => format!("タスク {} の優先度を{}に設定しました", id, priority),
+            Language::Chinese => format!("任务 {} 的优先级已设为{}", id, priority),
+        }
+    }
+
+    fn invalid_priority(&self, value: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid priority \"{}\", use H/M/L/N or 1-4", value),
+            Language::Japanese => format!("無効な優先度 \"{}\"。H/M/L/N または 1-4 を使用してください", value),
+            Language::Chinese => format!("无效的优先级 \"{}\"，请使用 H/M/L/N 或 1-4", value),
+        }
+    }
+
+    fn add_task_with_priority(&self) -> String {
+        match self.lang {
+            Language::English => "Add task with priority",
+            Language::Japanese => "優先度付きでタスクを追加",
+            Language::Chinese => "添加带优先级的任务",
+        }.to_string()
+    }
+
+    fn set_priority_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Set task priority (H/M/L/N or 1-4)",
+            Language::Japanese => "タスクの優先度を設定（H/M/L/N または 1-4）",
+            Language::Chinese => "设置任务优先级（H/M/L/N 或 1-4）",
+        }.to_string()
+    }
+
+    fn add_task_with_time(&self) -> String {
+        match self.lang {
+            Language::English => "Add task due at a time of day",
+            Language::Japanese => "時刻付きの期限でタスクを追加",
+            Language::Chinese => "添加带具体时间的截止任务",
+        }.to_string()
+    }
+
+    fn parse_date_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Check what a date expression resolves to",
+            Language::Japanese => "日付の表現がどの日付になるかを確認",
+            Language::Chinese => "查看日期表达式对应的日期",
+        }.to_string()
+    }
+
+    fn weekday_name(&self, weekday: Weekday) -> String {
+        let index = weekday.num_days_from_monday() as usize;
+        match self.lang {
+            Language::English => ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"][index],
+            Language::Japanese => ["月曜日", "火曜日", "水曜日", "木曜日", "金曜日", "土曜日", "日曜日"][index],
+            Language::Chinese => ["星期一", "星期二", "星期三", "星期四", "星期五", "星期六", "星期日"][index],
+        }.to_string()
+    }
+
+    fn repeat(&self) -> String {
+        match self.lang {
+            Language::English => "Repeat",
+            Language::Japanese => "繰り返し",
+            Language::Chinese => "重复",
+        }.to_string()
+    }
+
+    fn parent_task(&self) -> String {
+        match self.lang {
+            Language::English => "Parent",
+            Language::Japanese => "親タスク",
+            Language::Chinese => "父任务",
+        }.to_string()
+    }
+
+    fn subtasks(&self) -> String {
+        match self.lang {
+            Language::English => "Subtasks",
+            Language::Japanese => "サブタスク",
+            Language::Chinese => "子任务",
+        }.to_string()
+    }
+
+    fn add_subtask_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Add a subtask to task 3 (shown as 3.1, 3.2, ...)",
+            Language::Japanese => "タスク3にサブタスクを追加（3.1、3.2…と表示）",
+            Language::Chinese => "为任务3添加子任务（显示为 3.1、3.2 …）",
+        }.to_string()
+    }
+
+    fn done_with_subtasks_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Complete a task together with its subtasks",
+            Language::Japanese => "サブタスクも含めてタスクを完了",
+            Language::Chinese => "完成任务及其所有子任务",
+        }.to_string()
+    }
+
+    fn next_occurrence(&self) -> String {
+        match self.lang {
+            Language::English => "Next due",
+            Language::Japanese => "次回期限",
+            Language::Chinese => "下次截止",
+        }.to_string()
+    }
+
+    fn repeat_updated(&self, id: &str) -> String {
+        match self.lang {
+            Language::English => format!("Repeat rule of task {} has been updated", id),
+            Language::Japanese => format!("タスク {} の繰り返しルールが更新されました", id),
+            Language::Chinese => format!("已更新任务 {} 的重复规则", id),
+        }
+    }
+
+    fn invalid_repeat(&self, rule: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid repeat rule \"{}\", use daily, weekly[:mon,thu], monthly[:15], every:3d, after:1w or none", rule),
+            Language::Japanese => format!("無効な繰り返しルール \"{}\"。daily、weekly[:mon,thu]、monthly[:15]、every:3d、after:1w、none を使用してください", rule),
+            Language::Chinese => format!("无效的重复规则 \"{}\"，请使用 daily、weekly[:mon,thu]、monthly[:15]、every:3d、after:1w 或 none", rule),
+        }
+    }
+
+    fn add_repeating_task(&self) -> String {
+        match self.lang {
+            Language::English => "Add a repeating task",
+            Language::Japanese => "繰り返しタスクを追加",
+            Language::Chinese => "添加重复任务",
+        }.to_string()
+    }
+
+    fn invalid_filter(&self, token: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid filter \"{}\"", token),
+            Language::Japanese => format!("無効なフィルター \"{}\"", token),
+            Language::Chinese => format!("无效的过滤条件 \"{}\"", token),
+        }
+    }
+
+    fn option_in_filter(&self, option: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid filter \"{}\", options must come before the filter expression", option),
+            Language::Japanese => format!("無効なフィルター \"{}\"。オプションはフィルター式の前に指定してください", option),
+            Language::Chinese => format!("无效的过滤条件 \"{}\"，选项需要写在过滤表达式之前", option),
+        }
+    }
+
+    fn incomplete_filter(&self) -> String {
+        match self.lang {
+            Language::English => "Incomplete filter expression",
+            Language::Japanese => "フィルター式が不完全です",
+            Language::Chinese => "过滤表达式不完整",
+        }.to_string()
+    }
+
+    fn filter_expression_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Filter with an expression (and/or/not)",
+            Language::Japanese => "式でタスクを絞り込み（and/or/not）",
+            Language::Chinese => "使用表达式筛选任务（and/or/not）",
+        }.to_string()
+    }
+
+    fn filter_overdue_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show only overdue tasks",
+            Language::Japanese => "期限切れのタスクのみ表示",
+            Language::Chinese => "只显示已过期的任务",
+        }.to_string()
+    }
+
+    fn today(&self) -> String {
+        match self.lang {
+            Language::English => "today",
+            Language::Japanese => "今日",
+            Language::Chinese => "今天",
+        }.to_string()
+    }
+
+    fn tomorrow(&self) -> String {
+        match self.lang {
+            Language::English => "tomorrow",
+            Language::Japanese => "明日",
+            Language::Chinese => "明天",
+        }.to_string()
+    }
+
+    fn view_today(&self) -> String {
+        match self.lang {
+            Language::English => "Today:",
+            Language::Japanese => "今日のタスク:",
+            Language::Chinese => "今日任务:",
+        }.to_string()
+    }
+
+    fn view_week(&self) -> String {
+        match self.lang {
+            Language::English => "This week:",
+            Language::Japanese => "今週のタスク:",
+            Language::Chinese => "本周任务:",
+        }.to_string()
+    }
+
+    fn view_overdue(&self) -> String {
+        match self.lang {
+            Language::English => "Overdue:",
+            Language::Japanese => "期限切れのタスク:",
+            Language::Chinese => "已过期任务:",
+        }.to_string()
+    }
+
+    fn view_upcoming(&self, days: u32) -> String {
+        match self.lang {
+            Language::English => format!("Due in the next {} day(s):", days),
+            Language::Japanese => format!("{} 日以内に期限のタスク:", days),
+            Language::Chinese => format!("{} 天内到期的任务:", days),
+        }
+    }
+
+    fn view_today_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Tasks due today and overdue",
+            Language::Japanese => "今日が期限のタスクと期限切れのタスク",
+            Language::Chinese => "今天到期和已过期的任务",
+        }.to_string()
+    }
+
+    fn view_week_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Tasks due this week, grouped by day",
+            Language::Japanese => "今週が期限のタスク（日付ごと）",
+            Language::Chinese => "本周到期的任务（按天分组）",
+        }.to_string()
+    }
+
+    fn view_overdue_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Overdue tasks",
+            Language::Japanese => "期限切れのタスク",
+            Language::Chinese => "已过期的任务",
+        }.to_string()
+    }
+
+    fn view_upcoming_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Tasks due in the next N days (default 7)",
+            Language::Japanese => "N日以内に期限のタスク（既定は7日）",
+            Language::Chinese => "N天内到期的任务（默认7天）",
+        }.to_string()
+    }
+
+    fn default_sort_set(&self, keys: &str) -> String {
+        match self.lang {
+            Language::English => format!("Default sort set to {}", keys),
+            Language::Japanese => format!("既定の並べ替えを {} に設定しました", keys),
+            Language::Chinese => format!("默认排序方式已设为 {}", keys),
+        }
+    }
+
+    fn default_sort_is(&self, keys: &str) -> String {
+        match self.lang {
+            Language::English => format!("Default sort: {}", keys),
+            Language::Japanese => format!("既定の並べ替え: {}", keys),
+            Language::Chinese => format!("默认排序方式: {}", keys),
+        }
+    }
+
+    fn sort_list_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Sort by several keys ('-' for descending)",
+            Language::Japanese => "複数のキーで並べ替え（'-'で降順）",
+            Language::Chinese => "按多个字段排序（'-'表示倒序）",
+        }.to_string()
+    }
+
+    fn default_sort_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Set the default sort (overdue first with urgency)",
+            Language::Japanese => "既定の並べ替えを設定（urgencyで期限切れを先頭に）",
+            Language::Chinese => "设置默认排序方式（urgency 使过期任务排在最前）",
+        }.to_string()
+    }
+
+    fn saved_views(&self) -> String {
+        match self.lang {
+            Language::English => "Saved views:",
+            Language::Japanese => "保存済みビュー:",
+            Language::Chinese => "已保存的视图:",
+        }.to_string()
+    }
+
+    fn view_saved(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("View \"{}\" saved", name),
+            Language::Japanese => format!("ビュー \"{}\" を保存しました", name),
+            Language::Chinese => format!("已保存视图 \"{}\"", name),
+        }
+    }
+
+    fn view_removed(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("View \"{}\" removed", name),
+            Language::Japanese => format!("ビュー \"{}\" を削除しました", name),
+            Language::Chinese => format!("已删除视图 \"{}\"", name),
+        }
+    }
+
+    fn view_not_found(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("View \"{}\" does not exist", name),
+            Language::Japanese => format!("ビュー \"{}\" は存在しません", name),
+            Language::Chinese => format!("视图 \"{}\" 不存在", name),
+        }
+    }
+
+    fn view_name_required(&self) -> String {
+        match self.lang {
+            Language::English => "Please provide a view name",
+            Language::Japanese => "ビュー名を指定してください",
+            Language::Chinese => "请提供视图名称",
+        }.to_string()
+    }
+
+    fn invalid_view_name(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid view name \"{}\", use letters, digits, '-' or '_' (not save/list/rm)", name),
+            Language::Japanese => format!("無効なビュー名 \"{}\"。英数字、'-'、'_' を使用してください（save/list/rm は不可）", name),
+            Language::Chinese => format!("无效的视图名称 \"{}\"，只能使用字母、数字、'-' 或 '_'（不能为 save/list/rm）", name),
+        }
+    }
+
+    fn invalid_column(&self, name: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid column \"{}\", use id, priority, description, due, tags, created or status", name),
+            Language::Japanese => format!("無効な列 \"{}\"。id、priority、description、due、tags、created、status を使用してください", name),
+            Language::Chinese => format!("无效的列 \"{}\"，请使用 id、priority、description、due、tags、created 或 status", name),
+        }
+    }
+
+    fn invalid_sort(&self, key: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid sort key \"{}\", use id, due, priority, created, description, urgency or completed (prefix '-' for descending)", key),
+            Language::Japanese => format!("無効な並べ替えキー \"{}\"。id、due、priority、created、description、urgency、completed を使用してください（降順は'-'を付ける）", key),
+            Language::Chinese => format!("无效的排序字段 \"{}\"，请使用 id、due、priority、created、description、urgency 或 completed（前加'-'表示倒序）", key),
+        }
+    }
+
+    fn view_save_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Save a named view",
+            Language::Japanese => "名前付きビューを保存",
+            Language::Chinese => "保存自定义视图",
+        }.to_string()
+    }
+
+    fn view_show_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show a saved view",
+            Language::Japanese => "保存したビューを表示",
+            Language::Chinese => "显示已保存的视图",
+        }.to_string()
+    }
+
+    fn view_manage_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "List or remove saved views",
+            Language::Japanese => "保存したビューの一覧・削除",
+            Language::Chinese => "列出或删除已保存的视图",
+        }.to_string()
+    }
+
+    fn search_results(&self, pattern: &str, count: usize) -> String {
+        match self.lang {
+            Language::English => format!("{} task(s) matching \"{}\":", count, pattern),
+            Language::Japanese => format!("\"{}\" に一致するタスク {} 件:", pattern, count),
+            Language::Chinese => format!("匹配 \"{}\" 的任务共 {} 个:", pattern, count),
+        }
+    }
+
+    fn no_search_results(&self, pattern: &str) -> String {
+        match self.lang {
+            Language::English => format!("No tasks matching \"{}\"", pattern),
+            Language::Japanese => format!("\"{}\" に一致するタスクはありません", pattern),
+            Language::Chinese => format!("没有匹配 \"{}\" 的任务", pattern),
+        }
+    }
+
+    fn invalid_regex(&self, error: &str) -> String {
+        match self.lang {
+            Language::English => format!("Invalid regular expression: {}", error),
+            Language::Japanese => format!("無効な正規表現: {}", error),
+            Language::Chinese => format!("无效的正则表达式: {}", error),
+        }
+    }
+
+    fn in_trash(&self) -> String {
+        match self.lang {
+            Language::English => "trash",
+            Language::Japanese => "ゴミ箱",
+            Language::Chinese => "回收站",
+        }.to_string()
+    }
+
+    fn search_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Search tasks (--fuzzy, --trash)",
+            Language::Japanese => "タスクを検索（--fuzzy、--trash）",
+            Language::Chinese => "搜索任务（--fuzzy、--trash）",
+        }.to_string()
+    }
+
+    fn search_regex_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Search with a regular expression",
+            Language::Japanese => "正規表現で検索",
+            Language::Chinese => "使用正则表达式搜索",
+        }.to_string()
+    }
+
+    fn trash(&self) -> String {
+        match self.lang {
+            Language::English => "Trash:",
+            Language::Japanese => "ゴミ箱:",
+            Language::Chinese => "回收站:",
+        }.to_string()
+    }
+
+    fn deleted_at(&self) -> String {
+        match self.lang {
+            Language::English => "Deleted at",
+            Language::Japanese => "削除日時",
+            Language::Chinese => "删除时间",
+        }.to_string()
+    }
+
+    fn tasks_restored(&self, ids: &[String]) -> String {
+        let id_list = ids.join(", ");
+        match self.lang {
+            Language::English => format!("Tasks {} restored from trash", id_list),
+            Language::Japanese => format!("タスク {} をゴミ箱から復元しました", id_list),
+            Language::Chinese => format!("已从回收站恢复任务 {}", id_list),
+        }
+    }
+
+    fn tasks_purged(&self, count: usize) -> String {
+        match self.lang {
+            Language::English => format!("{} task(s) permanently deleted", count),
+            Language::Japanese => format!("{} 件のタスクを完全に削除しました", count),
+            Language::Chinese => format!("已彻底删除 {} 个任务", count),
+        }
+    }
+
+    fn json_output_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Output as JSON/NDJSON (list, show and changes)",
+            Language::Japanese => "JSON/NDJSON形式で出力（一覧・詳細・変更）",
+            Language::Chinese => "以 JSON/NDJSON 格式输出（列表、详情和修改结果）",
+        }.to_string()
+    }
+
+    fn trash_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show deleted tasks",
+            Language::Japanese => "削除したタスクを表示",
+            Language::Chinese => "查看已删除的任务",
+        }.to_string()
+    }
+
+    fn restore_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Restore task from trash",
+            Language::Japanese => "ゴミ箱からタスクを復元",
+            Language::Chinese => "从回收站恢复任务",
+        }.to_string()
+    }
+
+    fn purge_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Permanently delete trashed tasks",
+            Language::Japanese => "ゴミ箱のタスクを完全に削除",
+            Language::Chinese => "彻底删除回收站中的任务",
+        }.to_string()
+    }
+
+    fn export_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Export tasks in todo.txt format (or --format json)",
+            Language::Japanese => "タスクを todo.txt 形式でエクスポート（--format json も可）",
+            Language::Chinese => "以 todo.txt 格式导出任务（也可用 --format json）",
+        }.to_string()
+    }
+
+    fn tasks_exported(&self, count: usize, path: &str) -> String {
+        match self.lang {
+            Language::English => format!("{} task(s) exported to {}", count, path),
+            Language::Japanese => format!("{} 件のタスクを {} にエクスポートしました", count, path),
+            Language::Chinese => format!("已导出 {} 个任务到 {}", count, path),
+        }
+    }
+
+    fn archive_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Move completed tasks to the archive",
+            Language::Japanese => "完了したタスクをアーカイブに移動",
+            Language::Chinese => "将已完成的任务移入归档",
+        }.to_string()
+    }
+
+    fn auto_archive_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Archive completed tasks automatically (off to disable)",
+            Language::Japanese => "完了したタスクを自動的にアーカイブ（off で無効）",
+            Language::Chinese => "自动归档已完成的任务（off 关闭）",
+        }.to_string()
+    }
+
+    fn list_archived_cmd(&self) -> String {
+        match self.lang {
+            Language::English => "Show archived tasks (also search --archived)",
+            Language::Japanese => "アーカイブしたタスクを表示（search --archived も可）",
+            Language::Chinese => "查看已归档的任务（也可用 search --archived）",
+        }.to_string()
+    }
+
+    fn tasks_archived(&self, count: usize) -> String {
+        match self.lang {
+            Language::English => format!("{} task(s) archived", count),
+            Language::Japanese => format!("{} 件のタスクをアーカイブしました", count),
+            Language::Chinese => format!("已归档 {} 个任务", count),
+        }
+    }
+
+    fn archived_tasks(&self) -> String {
+        match self.lang {
+            Language::English => "Archived tasks (read-only):",
+            Language::Japanese => "アーカイブ済みのタスク（読み取り専用）:",
+            Language::Chinese => "已归档的任务（只读）:",
+        }.to_string()
+    }
+
+    fn auto_archive_set(&self, duration: &str) -> String {
+        match self.lang {
+            Language::English => format!("Tasks completed more than {} ago will be archived automatically when a task is added", duration),
+            Language::Japanese => format!("完了から {} 以上経ったタスクは、タスクを追加するときに自動的にアーカイブされます", duration),
+            Language::Chinese => format!("完成超过 {} 的任务将在添加任务时自动归档", duration),
+        }
+    }
+
+    fn auto_archive_off(&self) -> String {
+        match self.lang {
+            Language::English => "Automatic archiving is off",
+            Language::Japanese => "自動アーカイブは無効です",
+            Language::Chinese => "自动归档已关闭",
+        }.to_string()
+    }
+
+    fn tasks_uncompleted(&self, ids: &[String]) -> String {
+        let id_list = ids.join(", ");
+        match self.lang {
+            Language::English => format!("Tasks {} marked as incomplete", id_list),
+            Language::Japanese => format!("タスク {} が未完了としてマークされました", id_list),
+            Language::Chinese => format!("任务 {} 已标记为未完成", id_list),
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct Config {
+    language: Language,
+    #[serde(default = "default_list_name")]
+    current_list: String,  // 未通过 --list-name 指定时使用的任务列表
+    #[serde(default)]
+    views: BTreeMap<String, SavedView>,  // 自定义视图，按名称保存
+    #[serde(default)]
+    sort: String,  // list 的默认排序方式，为空时按优先级和ID排序
+    #[serde(default)]
+    auto_archive: String,  // 自动归档完成超过此时长的任务（如 30d），为空时不自动归档
+    #[serde(skip)]
+    unreadable: bool,  // 配置文件无法解析，此时拒绝保存，以免覆盖原文件
+}
+
+// 自定义视图：过滤表达式保存原文，每次显示时重新解析，"fri" 等相对日期总是相对于当天
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedView {
+    filter: Vec<String>,
+    #[serde(default)]
+    sort: String,          // 与 --sort 相同的格式，如 "due,-priority"
+    #[serde(default = "default_columns")]
+    columns: Vec<Column>,
+}
+
+// 视图中显示的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Column {
+    Id,
+    Priority,
+    Description,
+    Due,
+    Tags,
+    Created,
+    Status,
+}
+
+fn default_columns() -> Vec<Column> {
+    vec![Column::Id, Column::Description, Column::Due, Column::Tags]
+}
+
+impl Column {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "id" => Some(Column::Id),
+            "priority" => Some(Column::Priority),
+            "description" | "desc" => Some(Column::Description),
+            "due" => Some(Column::Due),
+            "tags" => Some(Column::Tags),
+            "created" => Some(Column::Created),
+            "status" => Some(Column::Status),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Priority => "priority",
+            Column::Description => "description",
+            Column::Due => "due",
+            Column::Tags => "tags",
+            Column::Created => "created",
+            Column::Status => "status",
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            Column::Id => 5,
+            Column::Priority => 8,
+            Column::Description => 40,
+            Column::Due | Column::Created => 17,
+            Column::Tags => 20,
+            Column::Status => 10,
+        }
+    }
+
+    fn header(self, t: &Translations) -> String {
+        let header = match self {
+            Column::Id => t.id(),
+            Column::Priority => t.priority(),
+            Column::Description => t.description(),
+            Column::Due => t.due_date(),
+            Column::Tags => t.tags(),
+            Column::Created => t.created_at(),
+            Column::Status => t.status(),
+        };
+        fixed_width_string(&header, self.width())
+    }
+
+    // 先按显示宽度补齐再着色，避免颜色控制字符影响对齐
+    fn cell(self, task: &Task, t: &Translations) -> String {
+        let pad = |text: &str| fixed_width_string(text, self.width());
+        match self {
+            Column::Id if task.completed => pad(&task.display_id()).green().to_string(),
+            Column::Id => pad(&task.display_id()).blue().to_string(),
+            Column::Priority => match task.priority {
+                Priority::None => pad(""),
+                priority => pad(&t.priority_name(priority)).to_string(),
+            },
+            Column::Description => pad(&truncate_str(&task.description, self.width() - 4)),
+            Column::Due => match &task.due_date {
+                Some(due) if !task.completed => {
+                    let text = pad(&format_due(due));
+                    match get_due_status(Some(due)) {
+                        DueStatus::Overdue => text.red().bold().to_string(),
+                        DueStatus::Urgent => text.bright_red().to_string(),
+                        DueStatus::Soon => text.yellow().to_string(),
+                        _ => text,
+                    }
+                },
+                Some(due) => pad(&format_due(due)),
+                None => pad(&t.none()),
+            },
+            Column::Tags => {
+                let tags: Vec<String> = task.tags.iter().map(|tag| format!("+{}", tag)).collect();
+                pad(&tags.join(" ")).cyan().to_string()
+            },
+            Column::Created => pad(&task.created_at.format("%Y-%m-%d %H:%M").to_string()),
+            Column::Status if task.completed => pad(&strip_colors(&t.status_completed())).green().to_string(),
+            Column::Status => pad(&strip_colors(&t.status_incomplete())).yellow().to_string(),
+        }
+    }
+}
+
+// 解析列名列表，如 "id,due,description"
+fn parse_columns(spec: &str) -> Result<Vec<Column>, String> {
+    spec.split(',')
+        .map(|name| Column::from_name(name).ok_or_else(|| get_translations().invalid_column(name.trim())))
+        .collect()
+}
+
+// 排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortField {
+    Id,
+    Due,
+    Priority,
+    Created,
+    Description,
+    Urgency,  // 按截止日期状态：已过期、紧急、即将到期、正常、无截止日期
+    Completed,  // 按完成时间，未完成的任务排在最后
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SortKey {
+    field: SortField,
+    descending: bool,
+}
+
+// 解析排序方式，如 "due,-priority,created"，字段前加'-'表示倒序
+fn parse_sort(spec: &str) -> Result<Vec<SortKey>, String> {
+    spec.split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(|key| {
+            let key = key.trim().to_lowercase();
+            let (descending, name) = match key.strip_prefix('-') {
+                Some(name) => (true, name),
+                None => (false, key.as_str()),
+            };
+            let field = match name {
+                "id" => SortField::Id,
+                "due" => SortField::Due,
+                "priority" => SortField::Priority,
+                "created" => SortField::Created,
+                "description" | "desc" => SortField::Description,
+                "urgency" => SortField::Urgency,
+                "completed" | "done" => SortField::Completed,
+                _ => return Err(get_translations().invalid_sort(&key)),
+            };
+            Ok(SortKey { field, descending })
+        })
+        .collect()
+}
+
+// 按排序方式排列任务，没有截止日期（或完成时间）的任务总是排在最后，最后按ID排序
+fn sort_tasks(tasks: &mut [&Task], keys: &[SortKey]) {
+    tasks.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| {
+                let ordering = match key.field {
+                    SortField::Id => a.id.cmp(&b.id),
+                    SortField::Due => match (a.due_date, b.due_date) {
+                        (Some(x), Some(y)) => x.cmp(&y),
+                        (Some(_), None) => return Ordering::Less,
+                        (None, Some(_)) => return Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    },
+                    SortField::Priority => a.priority.cmp(&b.priority),
+                    SortField::Created => a.created_at.cmp(&b.created_at),
+                    SortField::Description => a.description.to_lowercase().cmp(&b.description.to_lowercase()),
+                    SortField::Urgency => get_due_status(a.due_date.as_ref()).cmp(&get_due_status(b.due_date.as_ref())),
+                    SortField::Completed => match (a.completed_at, b.completed_at) {
+                        (Some(x), Some(y)) => x.cmp(&y),
+                        (Some(_), None) => return Ordering::Less,
+                        (None, Some(_)) => return Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    },
+                };
+                if key.descending { ordering.reverse() } else { ordering }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    });
+}
+
+// 去掉翻译文本中的颜色，便于重新计算宽度
+fn strip_colors(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+fn default_list_name() -> String {
+    DEFAULT_LIST.to_string()
+}
+
+impl Config {
+    fn new() -> Self {
+        Self {
+            language: Language::Chinese,
+            current_list: default_list_name(),
+            views: BTreeMap::new(),
+            sort: String::new(),
+            auto_archive: String::new(),
+            unreadable: false,
+        }
+    }
+    
+    fn load() -> Self {
+        let config_path = get_config_path();
+        
+        if config_path.exists() {
+            match fs::read_to_string(&config_path) {
+                Ok(contents) => {
+                    match serde_json::from_str(&contents) {
+                        Ok(config) => return config,
+                        Err(e) => {
+                            // 配置文件损坏时保留一份副本，避免之后保存时丢失原内容
+                            let t = get_translations();
+                            let backup = backup_corrupt_file(&config_path);
+                            eprintln!("{}", t.error(&t.file_corrupt(
+                                &config_path.display().to_string(),
+                                &e.to_string(),
+                                &backup.display().to_string(),
+                            )));
+                            return Config { unreadable: true, ..Config::new() };
+                        },
+                    }
+                },
+                Err(_) => return Config::new(),
+            }
+        }
+        
+        Config::new()
+    }
+    
+    fn save(&self) -> Result<(), io::Error> {
+        let config_path = get_config_path();
+        if self.unreadable {
+            let t = get_translations();
+            return Err(io::Error::other(t.config_not_saved(&config_path.display().to_string())));
+        }
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        let contents = serde_json::to_string_pretty(self)?;
+        write_atomic(&config_path, contents.as_bytes())
+    }
+}
+
+// 在 $VISUAL 或 $EDITOR 指定的编辑器中编辑文本（未设置时使用vi，Windows上使用notepad），返回编辑后的内容
+// 临时文件名为 "前缀-随机值.扩展名"，扩展名便于编辑器识别格式
+fn edit_in_editor(initial: &str, prefix: &str, extension: &str) -> Result<String, io::Error> {
+    let editor = ["VISUAL", "EDITOR"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+
+    // 使用随机文件名并要求文件不存在，不会跟随他人预先放置的符号链接；Unix上只有本人可以读写
+    let path = std::env::temp_dir().join(format!("{}-{}.{}", prefix, Uuid::new_v4().simple(), extension));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(initial.as_bytes())?;
+
+    // 编辑器可以带参数，如 "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let result = std::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .and_then(|status| {
+            if status.success() {
+                fs::read_to_string(&path)
+            } else {
+                Err(io::Error::other(get_translations().editor_exited(&editor)))
+            }
+        });
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+// 原子写入：先写入同目录下的临时文件并fsync，再重命名覆盖目标文件
+// 这样即使中途崩溃或磁盘已满，原文件也不会被截断
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), io::Error> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // 同步目录项，确保重命名本身已落盘（仅Unix支持打开目录）
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+// 将无法解析的文件复制为 <文件名>.corrupt，返回副本路径
+fn backup_corrupt_file(path: &Path) -> PathBuf {
+    let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(".corrupt");
+    let backup_path = path.with_file_name(backup_name);
+    let _ = fs::copy(path, &backup_path);
+    backup_path
+}
+
+// 默认任务列表的名称，对应数据目录下的 tasks.json
+const DEFAULT_LIST: &str = "default";
+
+// 命令行 --data-dir 指定的数据目录（在加载配置之前从参数中预先解析）
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+// 显式指定的数据目录：--data-dir 优先，其次是环境变量 JODO_HOME
+fn explicit_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        return Some(dir.clone());
+    }
+    std::env::var_os("JODO_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// 旧版本使用的数据目录 ~/.jodo，存在时继续使用以保持兼容
+fn legacy_data_dir() -> PathBuf {
+    let mut path = dirs::home_dir().unwrap_or_default();
+    path.push(".jodo");
+    path
+}
+
+// 获取数据目录：显式指定 > 已存在的 ~/.jodo > XDG数据目录 (如 ~/.local/share/jodo)
+fn get_data_dir() -> PathBuf {
+    if let Some(dir) = explicit_data_dir() {
+        return dir;
+    }
+    let legacy = legacy_data_dir();
+    if legacy.exists() {
+        return legacy;
+    }
+    dirs::data_dir().map(|dir| dir.join("jodo")).unwrap_or(legacy)
+}
+
+// 获取配置文件路径
+fn get_config_path() -> PathBuf {
+    // 显式指定数据目录时，配置与数据放在一起，便于在项目中维护独立的列表
+    if let Some(dir) = explicit_data_dir() {
+        return dir.join("config.json");
+    }
+    let legacy = legacy_data_dir();
+    if legacy.exists() {
+        return legacy.join("config.json");
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("jodo").join("config.json"))
+        .unwrap_or_else(|| legacy.join("config.json"))
+}
+
+// 列表名只允许字母、数字、'-'和'_'，避免路径穿越
+fn is_valid_list_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+// 获取任务列表对应的文件路径，默认列表沿用 tasks.json，其余列表保存在 lists 目录下
+fn get_list_path(name: &str) -> PathBuf {
+    let data_dir = get_data_dir();
+    if name == DEFAULT_LIST {
+        data_dir.join("tasks.json")
+    } else {
+        data_dir.join("lists").join(format!("{}.json", name))
+    }
+}
+
+// 列出数据目录中所有的任务列表（默认列表总是存在）
+fn available_lists() -> Vec<String> {
+    let mut names = vec![DEFAULT_LIST.to_string()];
+    if let Ok(entries) = fs::read_dir(get_data_dir().join("lists")) {
+        let mut others: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.strip_suffix(".json").map(|n| n.to_string())
+            })
+            .filter(|name| name != DEFAULT_LIST && is_valid_list_name(name))
+            .collect();
+        others.sort();
+        names.extend(others);
+    }
+    names
+}
+
+// 全局语言设置
+static mut CURRENT_LANGUAGE: Language = Language::Chinese;
+
+fn get_translations() -> Translations {
+    unsafe {
+        Translations::new(CURRENT_LANGUAGE)
+    }
+}
+fn build_cli() -> Cli {
+    Cli {
+        task: None,
+        due_date: None,
+        language: None,
+        help: false,
+        version: false,
+        edit_id: None,
+        edit_content_arg: None,
+        edit_content: None,
+        complete_ids: Vec::new(),
+        undo_id: None,
+        star_id: None,
+        unstar_id: None,
+        delete_ids: Vec::new(),
+        list: false,
+        show_id: None,
+        multi_mode: false,
+        priority: None,
+        repeat: None,
+        parent: None,
+        list_name: None,
+        data_dir: None,
+        format: OutputFormat::Text,
+        command: None,
+    }
+}
+
+#[derive(Parser)]
+#[clap(name = "jodo")]
+#[clap(about = "", long_about = None)]
+#[clap(version)]
+#[clap(disable_help_flag = true, disable_version_flag = true, disable_help_subcommand = true)]
+struct Cli {
+    /// 任务内容 (直接添加任务)
+    #[clap(value_name = "CONTENT")]
+    task: Option<String>,
+
+    /// 设置任务截止日期 (格式: YYYY-MM-DD)
+    #[clap(short = 't', long = "time")]
+    due_date: Option<String>,
+
+    /// 设置语言 (zh-cn: 中文, en: 英文, ja: 日语)
+    #[clap(short = 'L', long = "language")]
+    language: Option<String>,
+
+    /// 显示详细的帮助信息
+    #[clap(short = 'h', long = "help", conflicts_with = "task")]
+    help: bool,
+
+    /// 显示版本信息
+    #[clap(short = 'v', short_alias = 'V', long = "version")]
+    version: bool,
+
+    /// 编辑任务内容
+    #[clap(short = 'e', long = "edit", value_name = "ID")]
+    edit_id: Option<String>,
+
+    /// 编辑任务时的新内容 (可以直接作为命令行参数)
+    #[clap(value_name = "CONTENT")]
+    edit_content_arg: Option<String>,
+
+    /// 编辑任务时的新内容 (通过--content参数)
     #[clap(long = "content", requires = "edit_id")]
     edit_content: Option<String>,
 
-    /// 标记任务为完成
-    #[clap(short = 'c', long = "complete", num_args = 1.., value_name = "ID", conflicts_with_all = &["edit_id", "task"])]
-    complete_ids: Vec<String>,
+    /// 标记任务为完成
+    #[clap(short = 'c', long = "complete", num_args = 1.., value_name = "ID", conflicts_with_all = &["edit_id", "task"])]
+    complete_ids: Vec<String>,
+
+    /// 标记任务为未完成
+    #[clap(short = 'u', long = "undo", value_name = "ID", conflicts_with_all = &["complete_ids", "edit_id", "task"])]
+    undo_id: Option<String>,
+
+    /// 将任务标记为重要（置顶）
+    #[clap(long = "star", value_name = "ID", conflicts_with_all = &["complete_ids", "undo_id", "edit_id", "task"])]
+    star_id: Option<String>,
+
+    /// 取消任务的重要标记
+    #[clap(long = "unstar", value_name = "ID", conflicts_with_all = &["star_id", "complete_ids", "undo_id", "edit_id", "task"])]
+    unstar_id: Option<String>,
+
+    /// 删除任务 (可多个ID，空格分隔)
+    #[clap(short = 'd', long = "delete", num_args = 1.., value_name = "ID", conflicts_with_all = &["edit_id", "complete_ids", "undo_id", "star_id", "unstar_id", "task"])]
+    delete_ids: Vec<String>,
+
+    /// 列出所有任务
+    #[clap(short = 'l', long = "list", conflicts_with_all = &["edit_id", "complete_ids", "delete_ids", "task"])]
+    list: bool,
+
+    /// 显示任务的详细信息
+    #[clap(long = "show", value_name = "ID", conflicts_with_all = &["edit_id", "complete_ids", "undo_id", "star_id", "unstar_id", "delete_ids", "task"])]
+    show_id: Option<String>,
+
+    /// 启用批量添加任务模式
+    #[clap(short = 'm', long = "multi", conflicts_with_all = &["edit_id", "complete_ids", "undo_id", "star_id", "unstar_id", "delete_ids", "task", "show_id"])]
+    multi_mode: bool,
+
+    /// 设置任务优先级 (H/M/L/N 或 1-4)，用于添加和编辑任务
+    #[clap(short = 'p', long = "priority", value_name = "LEVEL", value_parser = parse_priority)]
+    priority: Option<Priority>,
+
+    /// 设置重复规则 (daily, weekly:mon,thu, monthly:15, every:3d, after:1w, none)，用于添加和编辑任务
+    #[clap(long = "repeat", value_name = "RULE")]
+    repeat: Option<String>,
+
+    /// 添加为指定任务的子任务
+    #[clap(long = "parent", value_name = "ID")]
+    parent: Option<String>,
+
+    /// 使用指定名称的任务列表 (本次命令有效)
+    #[clap(long = "list-name", value_name = "NAME", global = true)]
+    list_name: Option<String>,
+
+    /// 指定数据目录 (也可以通过环境变量 JODO_HOME 设置)
+    #[clap(long = "data-dir", value_name = "DIR", global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// 输出格式 (text, json, ndjson, todotxt)，用于 list、show、export 和修改任务的命令
+    #[clap(long = "format", value_enum, default_value = "text", global = true)]
+    format: OutputFormat,
+
+    #[clap(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 添加任务
+    Add {
+        /// 任务内容
+        content: String,
+        /// 设置任务截止日期
+        #[clap(short = 't', long = "time")]
+        due_date: Option<String>,
+        /// 设置任务优先级 (H/M/L/N 或 1-4)
+        #[clap(short = 'p', long = "priority", value_name = "LEVEL", value_parser = parse_priority)]
+        priority: Option<Priority>,
+        /// 设置重复规则
+        #[clap(long = "repeat", value_name = "RULE")]
+        repeat: Option<String>,
+        /// 添加为指定任务的子任务，如 --parent 3
+        #[clap(long = "parent", value_name = "ID")]
+        parent: Option<String>,
+    },
+    /// 列出所有任务
+    List {
+        /// 过滤表达式，如 +backend -blocked 或 "due:<fri and +work and not starred"
+        #[clap(allow_hyphen_values = true)]
+        filters: Vec<String>,
+        /// 只显示截止日期早于指定日期的任务
+        #[clap(long = "due-before", value_name = "DATE")]
+        due_before: Option<String>,
+        /// 只显示截止日期晚于指定日期的任务
+        #[clap(long = "due-after", value_name = "DATE")]
+        due_after: Option<String>,
+        /// 只显示在指定日期及之后创建的任务
+        #[clap(long = "created-since", value_name = "DATE")]
+        created_since: Option<String>,
+        /// 只显示在指定日期及之后完成的任务，如 --since last-week
+        #[clap(long = "since", value_name = "DATE")]
+        since: Option<String>,
+        /// 在任务描述中搜索
+        #[clap(long = "search", value_name = "TEXT")]
+        search: Option<String>,
+        /// 只显示已过期的任务
+        #[clap(long = "overdue")]
+        overdue: bool,
+        /// 只显示重要（高优先级）任务
+        #[clap(long = "starred")]
+        starred: bool,
+        /// 只显示已完成的任务
+        #[clap(long = "completed")]
+        completed: bool,
+        /// 排序方式，如 due,-priority,created 或 urgency（默认使用配置中的排序方式）
+        #[clap(long = "sort", value_name = "KEYS")]
+        sort: Option<String>,
+        /// 显示已归档的任务
+        #[clap(long = "archived")]
+        archived: bool,
+    },
+    /// 查看或设置 list 的默认排序方式
+    DefaultSort {
+        /// 排序方式，如 urgency,due；设为 priority 恢复默认
+        keys: Option<String>,
+    },
+    /// 编辑任务备注（在 $EDITOR 中打开）
+    Note {
+        /// 任务ID
+        id: String,
+        /// 不打开编辑器，直接在备注末尾追加文本
+        #[clap(long = "append", value_name = "TEXT")]
+        append: Option<String>,
+    },
+    /// 设置任务依赖：被依赖的任务完成之前，该任务处于阻塞状态
+    Block {
+        /// 任务ID
+        id: String,
+        /// 必须先完成的任务ID
+        #[clap(long = "on", value_name = "ID", required = true, num_args = 1..)]
+        on: Vec<String>,
+    },
+    /// 移除任务依赖
+    Unblock {
+        /// 任务ID
+        id: String,
+        /// 要移除的依赖任务ID
+        #[clap(long = "on", value_name = "ID", required = true, num_args = 1..)]
+        on: Vec<String>,
+    },
+    /// 添加或移除任务标签
+    Tag {
+        /// 任务ID
+        id: String,
+        /// 标签修改，如 +a -b
+        #[clap(required = true, num_args = 1.., allow_hyphen_values = true, value_parser = parse_tag_change)]
+        changes: Vec<(bool, String)>,
+    },
+    /// 完成一个任务
+    Done {
+        /// 任务ID
+        id: String,
+        /// 同时完成所有子任务
+        #[clap(long = "children")]
+        children: bool,
+    },
+    /// 将完成的任务标记为未完成
+    Undo {
+        /// 任务ID
+        id: String,
+    },
+    /// 删除一个任务
+    Remove {
+        /// 任务ID
+        id: String,
+    },
+    /// 编辑一个任务
+    Edit {
+        /// 任务ID
+        id: String,
+        /// 新的任务内容
+        #[clap(required_unless_present_any = ["time", "priority", "repeat", "interactive"])]
+        content: Option<String>,
+        /// 设置任务截止日期 (格式: YYYY-MM-DD)
+        #[clap(short = 't', long = "time")]
+        time: Option<String>,
+        /// 设置任务优先级 (H/M/L/N 或 1-4)
+        #[clap(short = 'p', long = "priority", value_parser = parse_priority)]
+        priority: Option<Priority>,
+        /// 设置重复规则 (daily, weekly:mon,thu, monthly:15, every:3d, after:1w, none)
+        #[clap(long = "repeat")]
+        repeat: Option<String>,
+        /// 在 $EDITOR 中编辑任务的所有字段
+        #[clap(short = 'i', long = "interactive", conflicts_with_all = ["content", "time", "priority", "repeat"])]
+        interactive: bool,
+    },
+    /// 在 $EDITOR 中一次编辑多个任务
+    EditAll {
+        /// 过滤表达式，与 list 相同；省略时编辑所有任务
+        #[clap(allow_hyphen_values = true)]
+        filters: Vec<String>,
+    },
+    /// 标记任务为重要（置顶）
+    Star {
+        /// 任务ID
+        id: String,
+    },
+    /// 取消任务重要标记
+    Unstar {
+        /// 任务ID
+        id: String,
+    },
+    /// 显示任务的详细信息
+    Show {
+        /// 任务ID
+        id: String,
+    },
+    /// 撤销最近一次修改操作 (可多次执行)
+    UndoLast,
+    /// 重做最近一次被撤销的操作
+    Redo,
+    /// 显示操作日志
+    Log,
+    /// 搜索任务的描述和备注（包括已完成的任务）
+    Search {
+        /// 搜索内容，默认为不区分大小写的子串匹配
+        pattern: String,
+        /// 按正则表达式搜索
+        #[clap(long = "regex", conflicts_with = "fuzzy")]
+        regex: bool,
+        /// 模糊搜索：按顺序包含搜索内容中的所有字符即可
+        #[clap(long = "fuzzy")]
+        fuzzy: bool,
+        /// 同时搜索回收站中的任务
+        #[clap(long = "trash")]
+        trash: bool,
+        /// 搜索已归档的任务
+        #[clap(long = "archived", conflicts_with = "trash")]
+        archived: bool,
+    },
+    /// 自定义视图：view save <名称> [过滤表达式]、view list、view rm <名称>、view <名称>
+    View {
+        /// save、list、rm 或视图名称
+        action: String,
+        /// save/rm 时的视图名称
+        name: Option<String>,
+        /// save 时的过滤表达式，与 list 相同
+        #[clap(allow_hyphen_values = true)]
+        filters: Vec<String>,
+        /// 排序方式，如 due,-priority（用于 save）
+        #[clap(long = "sort", value_name = "KEYS")]
+        sort: Option<String>,
+        /// 显示的列，如 id,priority,description,due,tags,created,status（用于 save）
+        #[clap(long = "columns", value_name = "COLUMNS")]
+        columns: Option<String>,
+    },
+    /// 今天到期和已过期的任务
+    Today,
+    /// 本周（到周日）到期和已过期的任务
+    Week,
+    /// 已过期的任务
+    Overdue,
+    /// 今后几天内到期的任务
+    Upcoming {
+        /// 天数，默认为7天
+        #[clap(default_value_t = 7)]
+        days: u32,
+    },
+    /// 显示任务统计：数量、每天（或每周）完成的任务、平均用时、按时完成率和各标签的情况
+    Stats {
+        /// 统计最近几天 (1-366)，默认为14天
+        #[clap(long = "days", value_name = "N", default_value_t = 14, conflicts_with = "weeks", value_parser = clap::value_parser!(u32).range(1..=366))]
+        days: u32,
+        /// 改为按周统计最近几周 (1-104)
+        #[clap(long = "weeks", value_name = "N", value_parser = clap::value_parser!(u32).range(1..=104))]
+        weeks: Option<u32>,
+    },
+    /// 显示回收站中的任务
+    Trash,
+    /// 从回收站恢复任务
+    Restore {
+        /// 回收站中的序号或任务UID (可多个)
+        #[clap(required = true, num_args = 1..)]
+        ids: Vec<String>,
+    },
+    /// 彻底删除回收站中的任务
+    Purge {
+        /// 只删除在此时长之前移入回收站的任务 (如 30d、2w、12h)
+        #[clap(long = "older-than", value_name = "DURATION")]
+        older_than: Option<String>,
+    },
+    /// 导出所有任务，默认为 todo.txt 格式（--format json/ndjson 导出为JSON）
+    Export {
+        /// 写入指定文件，省略时输出到标准输出
+        #[clap(short = 'o', long = "output", value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// 导出已归档的任务
+        #[clap(long = "archived")]
+        archived: bool,
+    },
+    /// 将已完成的任务移入归档文件，不再占用列表中的ID
+    Archive {
+        /// 只归档在此时长之前完成的任务 (如 14d、2w)
+        #[clap(long = "older-than", value_name = "DURATION")]
+        older_than: Option<String>,
+    },
+    /// 查看或设置自动归档：完成超过指定时长的任务在添加任务时自动归档
+    AutoArchive {
+        /// 时长，如 30d；设为 off 关闭自动归档
+        duration: Option<String>,
+    },
+    /// 列出所有任务列表
+    Lists,
+    /// 切换默认使用的任务列表
+    Switch {
+        /// 任务列表名称
+        name: String,
+    },
+    /// 检查日期表达式解析后的结果
+    ParseDate {
+        /// 日期表达式，如 "next monday 9am"
+        #[clap(required = true, num_args = 1.., allow_hyphen_values = true)]
+        text: Vec<String>,
+    },
+    /// 显示帮助信息
+    Help,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Task {
+    id: usize,
+    #[serde(default = "Uuid::new_v4")]
+    uid: Uuid,        // 永久标识，不随reassign_ids变化
+    description: String,
+    completed: bool,
+    created_at: DateTime<Local>,
+    due_date: Option<DateTime<Local>>,
+    #[serde(default)]
+    local_due: bool,  // 截止日期按本地时区保存；旧版本把本地零点当作UTC保存，读取时需要修正
+    starred: bool,    
+    deleted: bool,    
+    #[serde(default)]
+    deleted_at: Option<DateTime<Local>>,  // 移入回收站的时间
+    #[serde(default)]
+    tags: Vec<String>,  // 标签，统一为小写
+    #[serde(default)]
+    priority: Priority,  // 优先级，starred 与 High 保持同步以兼容旧版本
+    #[serde(default)]
+    repeat: Option<Repeat>,  // 重复规则，完成时生成下一次任务
+    #[serde(default)]
+    parent: Option<Uuid>,  // 父任务的UID，子任务（清单项）只有一层
+    #[serde(skip)]
+    parent_id: usize,  // 父任务的显示ID，由reassign_ids设置，0表示顶层任务
+    #[serde(default)]
+    depends_on: Vec<Uuid>,  // 必须先完成的任务的UID
+    #[serde(default)]
+    notes: String,  // 多行备注，如链接和命令
+    #[serde(default)]
+    completed_at: Option<DateTime<Local>>,  // 完成时间，取消完成时清除
+}
+
+// 任务的重复规则
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Repeat {
+    Daily,                              // 每天
+    Weekly { weekdays: Vec<Weekday> },  // 每周的指定几天
+    Monthly { day: u32 },               // 每月第N天，超出当月天数时取月末
+    Every { days: i64 },                // 从截止日期起每隔N天
+    After { days: i64 },                // 完成后N天
+}
+
+impl Repeat {
+    // 用首次截止日期补全未指定的星期或日期（如 "weekly"、"monthly"）
+    fn anchored(self, date: NaiveDate) -> Self {
+        match self {
+            Repeat::Weekly { weekdays } if weekdays.is_empty() => Repeat::Weekly { weekdays: vec![date.weekday()] },
+            Repeat::Monthly { day: 0 } => Repeat::Monthly { day: date.day() },
+            rule => rule,
+        }
+    }
+
+    // 未指定截止日期时，今天或之后第一个符合规则的日期
+    fn first_date(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Repeat::Weekly { weekdays } if !weekdays.is_empty() && !weekdays.contains(&today.weekday()) => {
+                self.next_date(today, today)
+            },
+            Repeat::Monthly { day } if *day > 0 => {
+                let this_month = clamp_day(today.year(), today.month(), *day);
+                if this_month >= today { this_month } else { self.next_date(today, today) }
+            },
+            _ => today,
+        }
+    }
+
+    // 根据本次截止日期和完成日期计算下一次的截止日期
+    fn next_date(&self, due: NaiveDate, completed: NaiveDate) -> NaiveDate {
+        match self {
+            Repeat::Daily => due + chrono::Duration::days(1),
+            Repeat::Weekly { weekdays } => (1..=7)
+                .map(|n| due + chrono::Duration::days(n))
+                .find(|d| weekdays.contains(&d.weekday()))
+                .unwrap_or(due + chrono::Duration::weeks(1)),
+            Repeat::Monthly { day } => {
+                let (year, month) = if due.month() == 12 { (due.year() + 1, 1) } else { (due.year(), due.month() + 1) };
+                clamp_day(year, month, *day)
+            },
+            Repeat::Every { days } => due + chrono::Duration::days(*days),
+            Repeat::After { days } => completed + chrono::Duration::days(*days),
+        }
+    }
+
+    // 今天完成时的下一次截止日期：逾期完成时按规则继续推算，直到在今天之后，避免生成已经过期的任务
+    fn next_date_after(&self, due: NaiveDate, today: NaiveDate) -> NaiveDate {
+        let mut next_date = self.next_date(due, today);
+        while next_date <= today {
+            let following = self.next_date(next_date, today);
+            if following <= next_date {
+                break;
+            }
+            next_date = following;
+        }
+        next_date
+    }
+}
+
+// 规则的文本形式与 --repeat 的语法一致
+impl std::fmt::Display for Repeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Repeat::Daily => write!(f, "daily"),
+            Repeat::Weekly { weekdays } => {
+                let days: Vec<String> = weekdays.iter().map(|d| d.to_string().to_lowercase()).collect();
+                write!(f, "weekly:{}", days.join(","))
+            },
+            Repeat::Monthly { day } => write!(f, "monthly:{}", day),
+            Repeat::Every { days } => write!(f, "every:{}d", days),
+            Repeat::After { days } => write!(f, "after:{}d", days),
+        }
+    }
+}
+
+// 某月的第day天，超出当月天数时取月末
+fn clamp_day(year: i32, month: u32, day: u32) -> NaiveDate {
+    (1..=day.min(31)).rev()
+        .find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+        .unwrap_or_default()
+}
+
+// every:/after: 的最长间隔（100年），避免计算截止日期时溢出
+const MAX_REPEAT_DAYS: i64 = 36525;
+
+// 解析重复规则：daily、weekly[:mon,thu]、monthly[:15]、every:3d、after:2w，"none" 表示清除规则
+fn parse_repeat(rule: &str) -> Result<Option<Repeat>, String> {
+    let t = get_translations();
+    let rule = rule.trim().to_lowercase();
+    let (kind, arg) = match rule.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg.trim())),
+        None => (rule.as_str(), None),
+    };
+    let interval_days = |arg: Option<&str>| {
+        arg.and_then(parse_duration)
+            .map(|d| d.num_days())
+            .filter(|days| (1..=MAX_REPEAT_DAYS).contains(days))
+    };
+
+    let repeat = match (kind, arg) {
+        ("none", None) => return Ok(None),
+        ("daily", None) => Some(Repeat::Daily),
+        ("weekly", None) => Some(Repeat::Weekly { weekdays: Vec::new() }),
+        ("weekly", Some(days)) => days.split(',')
+            .map(|d| d.trim().parse::<Weekday>().ok())
+            .collect::<Option<Vec<_>>>()
+            .filter(|weekdays| !weekdays.is_empty())
+            .map(|weekdays| Repeat::Weekly { weekdays }),
+        ("monthly", None) => Some(Repeat::Monthly { day: 0 }),
+        ("monthly", Some(day)) => day.parse::<u32>().ok()
+            .filter(|day| (1..=31).contains(day))
+            .map(|day| Repeat::Monthly { day }),
+        ("every", arg) => interval_days(arg).map(|days| Repeat::Every { days }),
+        ("after", arg) => interval_days(arg).map(|days| Repeat::After { days }),
+        _ => None,
+    };
+    repeat.map(Some).ok_or_else(|| t.invalid_repeat(&rule))
+}
+
+// 将本地日期和时间转换为本地时区的时间点（夏令时重叠时取较早的一个）
+fn local_datetime(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+// 任务优先级，声明顺序即排序顺序（高优先级在前）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+enum Priority {
+    High,
+    Medium,
+    Low,
+    #[default]
+    None,
+}
+
+impl Priority {
+    // 列表中优先级列的标记
+    fn marker(self) -> ColoredString {
+        match self {
+            Priority::High => "H ".red().bold(),
+            Priority::Medium => "M ".yellow(),
+            Priority::Low => "L ".cyan(),
+            Priority::None => "  ".normal(),
+        }
+    }
+}
+
+// 解析优先级：支持 H/M/L/N、high/medium/low/none 以及 1-4
+fn parse_priority(s: &str) -> Result<Priority, String> {
+    match s.trim().to_lowercase().as_str() {
+        "h" | "high" | "1" => Ok(Priority::High),
+        "m" | "medium" | "2" => Ok(Priority::Medium),
+        "l" | "low" | "3" => Ok(Priority::Low),
+        "n" | "none" | "4" => Ok(Priority::None),
+        _ => Err(get_translations().invalid_priority(s)),
+    }
+}
+
+impl Task {
+    // 设置优先级，同时更新兼容旧版本的 starred 字段
+    fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+        self.starred = priority == Priority::High;
+    }
+
+    // 设置重复规则，没有截止日期的任务从第一个符合规则的日期开始
+    fn set_repeat(&mut self, repeat: Option<Repeat>) {
+        if let (Some(rule), None) = (&repeat, self.due_date) {
+            let first = rule.first_date(Local::now().date_naive());
+            self.due_date = local_datetime(first, NaiveTime::MIN);
+        }
+        let anchor = self.due_date.unwrap_or_else(Local::now).date_naive();
+        self.repeat = repeat.map(|rule| rule.anchored(anchor));
+    }
+
+    // 显示ID：已完成任务带'c'后缀，子任务为"父任务ID.序号"
+    fn display_id(&self) -> String {
+        if self.parent_id > 0 {
+            format!("{}.{}", self.parent_id, self.id)
+        } else if self.completed {
+            format!("{}c", self.id)
+        } else {
+            self.id.to_string()
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct TodoList {
+    tasks: Vec<Task>,
+    file_path: PathBuf,   
+    next_id: usize,      
+    #[serde(skip)]
+    last_synced: String,  // 最近一次读取或写入的文件内容，用于检测其他进程的修改
+    #[serde(skip)]
+    last_changes: Vec<Task>,  // 最近一次保存时发生变化的任务，用于输出操作结果
+}
+
+// 命令输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,   // 彩色表格和提示信息
+    Json,   // 单个JSON文档 {"schema_version": 1, "tasks": [...]}
+    Ndjson, // 每行一个任务对象，每个对象都带有 schema_version
+    Todotxt, // 每行一个 todo.txt 格式的任务
+}
+
+// JSON输出格式的版本号，字段含义变化时递增；与 tasks.json 的存储格式相互独立
+const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+// JSON输出中的任务对象
+#[derive(Serialize)]
+struct TaskOutput<'a> {
+    id: Option<String>,  // 显示ID（已完成任务带'c'后缀），回收站中的任务为null
+    uid: Uuid,
+    description: &'a str,
+    completed: bool,
+    starred: bool,
+    deleted: bool,
+    created_at: DateTime<Local>,
+    due_date: Option<DateTime<Local>>,
+    deleted_at: Option<DateTime<Local>>,
+    tags: &'a [String],
+    priority: Priority,
+    repeat: Option<String>,  // 与 --repeat 语法相同的规则文本
+    parent: Option<Uuid>,  // 父任务的UID
+    depends_on: &'a [Uuid],  // 依赖的任务UID
+    notes: &'a str,
+    completed_at: Option<DateTime<Local>>,
+}
+
+impl<'a> From<&'a Task> for TaskOutput<'a> {
+    fn from(task: &'a Task) -> Self {
+        let id = if task.deleted {
+            None
+        } else {
+            Some(task.display_id())
+        };
+        Self {
+            id,
+            uid: task.uid,
+            description: &task.description,
+            completed: task.completed,
+            starred: task.starred,
+            deleted: task.deleted,
+            created_at: task.created_at,
+            due_date: task.due_date,
+            deleted_at: task.deleted_at,
+            tags: &task.tags,
+            priority: task.priority,
+            repeat: task.repeat.as_ref().map(|r| r.to_string()),
+            parent: task.parent,
+            depends_on: &task.depends_on,
+            notes: &task.notes,
+            completed_at: task.completed_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OutputDocument<'a> {
+    schema_version: u32,
+    tasks: Vec<TaskOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct OutputLine<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    task: TaskOutput<'a>,
+}
+
+// 以JSON、NDJSON或todo.txt格式输出任务
+fn print_tasks(tasks: &[&Task], format: OutputFormat) {
+    print!("{}", render_tasks(tasks, format));
+}
+
+// 将任务转换为JSON、NDJSON或todo.txt格式的文本，以换行结尾
+fn render_tasks(tasks: &[&Task], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Ndjson => tasks.iter()
+            .map(|task| OutputLine { schema_version: OUTPUT_SCHEMA_VERSION, task: TaskOutput::from(*task) })
+            .filter_map(|line| serde_json::to_string(&line).ok())
+            .map(|json| json + "\n")
+            .collect(),
+        OutputFormat::Todotxt => tasks.iter()
+            .map(|task| todotxt_line(task) + "\n")
+            .collect(),
+        _ => {
+            let document = OutputDocument {
+                schema_version: OUTPUT_SCHEMA_VERSION,
+                tasks: tasks.iter().map(|task| TaskOutput::from(*task)).collect(),
+            };
+            serde_json::to_string_pretty(&document)
+                .map(|json| json + "\n")
+                .unwrap_or_default()
+        },
+    }
+}
+
+// 将任务转换为一行 todo.txt：完成标记和完成日期、优先级、创建日期、描述、标签和 due: 截止日期
+// 高/中/低优先级对应 (A)/(B)/(C)，已完成任务按 todo.txt 的惯例以 pri:A 保留优先级
+// 标签导出为 +项目，以 @ 开头的标签（如 +@phone）导出为上下文 @phone
+// 重复规则、UID、父任务和依赖分别写为 rec:、uid:、parent:、dep:，备注不导出
+fn todotxt_line(task: &Task) -> String {
+    let letter = match task.priority {
+        Priority::High => Some('A'),
+        Priority::Medium => Some('B'),
+        Priority::Low => Some('C'),
+        Priority::None => None,
+    };
+
+    let mut parts = Vec::new();
+    if task.completed {
+        // 旧版本完成的任务没有完成时间；todo.txt 中创建日期之前必须有完成日期，因此以创建日期代替
+        let completed_at = task.completed_at.unwrap_or(task.created_at);
+        parts.push(format!("x {}", completed_at.format("%Y-%m-%d")));
+    } else if let Some(letter) = letter {
+        parts.push(format!("({})", letter));
+    }
+    parts.push(task.created_at.format("%Y-%m-%d").to_string());
+    // todo.txt 每行一个任务，描述中的换行等空白合并为一个空格
+    parts.push(task.description.split_whitespace().map(todotxt_escape).collect::<Vec<_>>().join(" "));
+
+    parts.extend(task.tags.iter().map(|tag| {
+        if tag.starts_with('@') { tag.clone() } else { format!("+{}", tag) }
+    }));
+    if let Some(due) = task.due_date {
+        parts.push(format!("due:{}", due.format("%Y-%m-%d")));
+        // due: 只能是日期，一天中的时刻另外保存
+        if has_due_time(&due) {
+            parts.push(format!("due-time:{}", due.format("%H:%M")));
+        }
+    }
+    if let Some(repeat) = &task.repeat {
+        parts.push(format!("rec:{}", todotxt_rec(repeat, task.due_date.map(|due| due.date_naive()))));
+    }
+    parts.push(format!("uid:{}", task.uid));
+    if let Some(parent) = task.parent {
+        parts.push(format!("parent:{}", parent));
+    }
+    parts.extend(task.depends_on.iter().map(|uid| format!("dep:{}", uid)));
+    if let (true, Some(letter)) = (task.completed, letter) {
+        parts.push(format!("pri:{}", letter));
+    }
+    parts.join(" ")
+}
+
+// 描述中会被 todo.txt 应用解析的词（+项目、@上下文、key:value）前面加反斜杠，
+// 原本以反斜杠开头的词也加一个，读取时去掉每个词开头的一个反斜杠即可还原；网址照原样保留
+fn todotxt_escape(word: &str) -> String {
+    let syntax = word.len() > 1 && word.starts_with(['+', '@', '\\']);
+    let key_value = word.split_once(':')
+        .is_some_and(|(key, value)| !key.is_empty() && !value.is_empty() && !value.starts_with("//"));
+    if syntax || key_value { format!("\\{}", word) } else { word.to_string() }
+}
+
+// 重复规则的 rec: 值：能用 todo.txt 惯例表示的写为 +1d、+3d（按截止日期）、3d（按完成日期）、+1w、+1m，
+// 其余（如每周多天、每月与截止日期不同的一天）按 --repeat 的语法写出，如 rec:weekly:mon,thu
+fn todotxt_rec(repeat: &Repeat, due: Option<NaiveDate>) -> String {
+    match repeat {
+        Repeat::Daily => "+1d".to_string(),
+        Repeat::Every { days } => format!("+{}d", days),
+        Repeat::After { days } => format!("{}d", days),
+        Repeat::Weekly { weekdays } if due.is_some_and(|due| weekdays == &[due.weekday()]) => "+1w".to_string(),
+        Repeat::Monthly { day } if due.is_some_and(|due| due.day() == *day) => "+1m".to_string(),
+        rule => rule.to_string(),
+    }
+}
+
+// 添加新任务（直接添加或 add 子命令），parent为父任务ID时添加为子任务
+fn add_from_args(todo_list: &mut TodoList, format: OutputFormat, task_str: &str, due_str: Option<&str>, priority: Option<Priority>, repeat_str: Option<&str>, parent: Option<&str>) {
+    let t = get_translations();
+    let parent = match parent {
+        Some(id_str) => match todo_list.get_task(id_str) {
+            Ok(task) if task.parent.is_none() => Some(task.uid),
+            Ok(_) => {
+                eprintln!("{}", t.error(t.nested_subtask()));
+                return;
+            },
+            Err(e) => {
+                eprintln!("{}", t.error(e));
+                return;
+            },
+        },
+        None => None,
+    };
+
+    let due_date = match due_str {
+        Some(date_str) => match parse_date(date_str) {
+            Ok(date) => Some(date),
+            Err(e) => {
+                eprintln!("{}", t.error(e));   
+                return;      
+            }
+        },  
+        None => None,        
+    };
+
+    // 重复任务未指定截止日期时，从第一个符合规则的日期开始
+    let repeat = match repeat_str.map(parse_repeat) {
+        Some(Ok(repeat)) => repeat,
+        Some(Err(e)) => {
+            eprintln!("{}", t.error(&e));
+            return;
+        },
+        None => None,
+    };
+    let due_date = match (due_date, &repeat) {
+        (None, Some(rule)) => local_datetime(rule.first_date(Local::now().date_naive()), NaiveTime::MIN),
+        (due_date, _) => due_date,
+    };
+    let repeat = repeat.map(|rule| rule.anchored(due_date.unwrap_or_else(Local::now).date_naive()));
+
+    let (description, tags) = extract_tags(task_str);
+    if let Err(e) = todo_list.add_task(description.clone(), due_date, tags, priority.unwrap_or_default(), repeat.clone(), parent) {
+        eprintln!("{}", t.error(&e.to_string()));
+    } else {
+        print_result(todo_list, format, &t.task_added(&description));
+        if format == OutputFormat::Text {
+            if let Some(date_str) = due_str {
+                println!("{}: {}", t.due_date(), date_str);
+            } else if let Some(date) = due_date {
+                println!("{}: {}", t.due_date(), format_due(&date));
+            }
+            if let Some(rule) = &repeat {
+                println!("{}: {}", t.repeat(), rule);
+            }
+        }
+    }   
+}
+
+// 输出修改操作的结果：文本格式显示提示信息，JSON格式输出受影响的任务
+fn print_result(todo_list: &TodoList, format: OutputFormat, message: &str) {
+    if format == OutputFormat::Text {
+        println!("{}", message);
+    } else {
+        let changed = todo_list.changed_tasks();
+        print_tasks(&changed.iter().collect::<Vec<_>>(), format);
+    }
+}
+
+// 操作日志中记录的修改类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Operation {
+    Add,
+    Edit,
+    Complete,
+    Reopen,
+    Star,
+    Unstar,
+    Delete,
+    Restore,
+    Purge,
+    Tag,
+    Block,
+    Unblock,
+    Note,
+    Archive,
+}
+
+// 一次修改操作：只保存发生变化的任务（按UID区分）在修改前后的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    operation: Operation,
+    at: DateTime<Local>,
+    before: Vec<Task>,  // 修改前的任务，新增的任务不在其中
+    after: Vec<Task>,   // 修改后的任务，被彻底删除的任务不在其中
+    order_before: Vec<Uuid>,  // 修改前按显示ID排列的任务UID，用于恢复原有顺序
+    order_after: Vec<Uuid>,   // 修改后按显示ID排列的任务UID
+}
+
+// 操作日志，用于多级撤销和重做
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    entries: Vec<JournalEntry>,
+    position: usize,  // 当前生效的操作数量，之后的记录可以重做
+}
+
+// 操作日志最多保留的记录数
+const MAX_JOURNAL_ENTRIES: usize = 100;
+
+impl Journal {
+    fn load(path: &Path) -> Self {
+        let contents = fs::read_to_string(path).unwrap_or_default();
+        if contents.trim().is_empty() {
+            return Journal::default();
+        }
+        // 日志损坏不影响任务数据：保留一份副本并提示，然后从空日志开始
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            let t = get_translations();
+            let backup = backup_corrupt_file(path);
+            eprintln!("{}", t.error(&t.journal_corrupt(
+                &path.display().to_string(),
+                &e.to_string(),
+                &backup.display().to_string(),
+            )));
+            Journal::default()
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), io::Error> {
+        let contents = serde_json::to_string_pretty(self)?;
+        write_atomic(path, contents.as_bytes())
+    }
+
+    // 记录新操作，丢弃所有已撤销的记录
+    fn record(&mut self, entry: JournalEntry) {
+        self.entries.truncate(self.position);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            let excess = self.entries.len() - MAX_JOURNAL_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.position = self.entries.len();
+    }
+}
+
+// 获取任务文件对应的操作日志路径，如 tasks.json -> tasks.journal.json
+fn get_journal_path(data_path: &Path) -> PathBuf {
+    let stem = data_path.file_stem().unwrap_or_default().to_string_lossy();
+    data_path.with_file_name(format!("{}.journal.json", stem))
+}
+
+// 获取任务列表对应的归档文件路径，如 tasks.archive.json
+fn get_archive_path(data_path: &Path) -> PathBuf {
+    let stem = data_path.file_stem().unwrap_or_default().to_string_lossy();
+    data_path.with_file_name(format!("{}.archive.json", stem))
+}
+
+// 比较两个任务列表，返回发生变化的任务在修改前后的内容
+// 显示ID会被reassign_ids重新分配，因此比较时忽略
+fn diff_tasks(old: &[Task], new: &[Task]) -> (Vec<Task>, Vec<Task>) {
+    let same_content = |a: &Task, b: &Task| Task { id: 0, parent_id: 0, ..a.clone() } == Task { id: 0, parent_id: 0, ..b.clone() };
+
+    let before = old.iter()
+        .filter(|o| !new.iter().any(|n| n.uid == o.uid && same_content(n, o)))
+        .cloned()
+        .collect();
+    let after = new.iter()
+        .filter(|n| !old.iter().any(|o| o.uid == n.uid && same_content(n, o)))
+        .cloned()
+        .collect();
+    (before, after)
+}
+
+// 按显示ID排列的任务UID
+fn display_order(tasks: &[Task]) -> Vec<Uuid> {
+    let mut visible: Vec<&Task> = tasks.iter().filter(|task| !task.deleted).collect();
+    visible.sort_by_key(|task| task.id);
+    visible.iter().map(|task| task.uid).collect()
+}
+
+// 将任务列表中的一组任务替换为另一组（按UID），并按给定顺序设置ID，用于撤销和重做
+fn apply_changes(tasks: &mut Vec<Task>, remove: &[Task], insert: &[Task], order: &[Uuid]) {
+    tasks.retain(|task| !remove.iter().any(|r| r.uid == task.uid));
+    tasks.extend(insert.iter().cloned());
+
+    // 不在顺序记录中的任务排在最后，保持原有的相对顺序
+    for task in tasks.iter_mut() {
+        task.id = match order.iter().position(|uid| *uid == task.uid) {
+            Some(pos) => pos + 1,
+            None => order.len() + task.id,
+        };
+    }
+}
+
+// 任务文件在加载后被其他jodo进程修改
+#[derive(Debug)]
+struct ListChanged;
+
+impl std::fmt::Display for ListChanged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", get_translations().list_changed())
+    }
+}
+
+impl std::error::Error for ListChanged {}
+
+// 将保存错误转换为提示信息，区分"列表已被修改"和普通的保存失败
+fn save_error(e: io::Error) -> &'static str {
+    let t = get_translations();
+    if e.get_ref().is_some_and(|inner| inner.is::<ListChanged>()) {
+        t.list_changed()
+    } else {
+        t.save_failed()
+    }
+}
+
+// 对任务文件加排他锁，返回的文件句柄被释放时自动解锁
+// 任务文件本身会被原子重命名替换，因此锁在单独的 .lock 文件上
+fn lock_tasks_file(data_path: &Path) -> Result<File, io::Error> {
+    let mut lock_name = data_path.file_name().unwrap_or_default().to_os_string();
+    lock_name.push(".lock");
+    let lock_file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(data_path.with_file_name(lock_name))?;
+    lock_file.lock_exclusive()?;
+    Ok(lock_file)
+}
+
+// 读取任务文件，返回任务列表和原始内容
+fn read_tasks_file(file_path: &Path) -> Result<(Vec<Task>, String), io::Error> {
+    if !file_path.exists() {
+        return Ok((Vec::new(), String::new()));
+    }
+
+    let mut file = File::open(file_path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    if contents.trim().is_empty() {
+        return Ok((Vec::new(), contents));
+    }
+
+    // 解析失败时拒绝继续，避免用空列表覆盖原有数据
+    let mut tasks: Vec<Task> = serde_json::from_str(&contents).map_err(|e| {
+        let t = get_translations();
+        let backup = backup_corrupt_file(file_path);
+        io::Error::new(io::ErrorKind::InvalidData, t.file_corrupt(
+            &file_path.display().to_string(),
+            &e.to_string(),
+            &backup.display().to_string(),
+        ))
+    })?;
+
+    // 旧版本把本地日期的零点当作UTC时间保存，按原本的本地日期重新转换
+    for task in tasks.iter_mut().filter(|t| !t.local_due) {
+        if let Some(due) = task.due_date {
+            let naive = due.naive_utc();
+            task.due_date = local_datetime(naive.date(), naive.time()).or(Some(due));
+        }
+        task.local_due = true;
+    }
+
+    // 旧版本只有星标，星标任务视为高优先级
+    for task in tasks.iter_mut().filter(|t| t.starred && t.priority == Priority::None) {
+        task.priority = Priority::High;
+    }
+    Ok((tasks, contents))
+}
+
+// 任务标识：显示用的短ID会在reassign_ids后变化，UID则永久不变
+#[derive(Debug, Clone, PartialEq)]
+enum TaskKey {
+    Display { id: usize, completed: bool }, // 显示ID及"是否完成"标志
+    Uid(String),                            // 完整UID或其前缀（小写）
+    Child { parent: usize, index: usize },  // 子任务，如 3.2 表示任务3的第2个子任务
+    Invalid,                                // 解析失败
+}
+
+// UID前缀至少需要的十六进制字符数，避免误匹配
+const MIN_UID_PREFIX_LEN: usize = 8;
+
+impl TaskKey {
+    // 判断任务是否与该标识匹配，check_completed为false时忽略显示ID的'c'后缀
+    fn matches(&self, task: &Task, check_completed: bool) -> bool {
+        match self {
+            TaskKey::Display { id, completed } => {
+                task.parent_id == 0 && task.id == *id && (!check_completed || task.completed == *completed)
+            },
+            TaskKey::Child { parent, index } => task.parent_id == *parent && task.id == *index,
+            TaskKey::Uid(prefix) => task.uid.to_string().starts_with(prefix.as_str()),
+            TaskKey::Invalid => false,
+        }
+    }
+}
+
+// 辅助函数：解析任务ID（支持后缀'c'表示已完成任务，也支持UID或以'@'开头的UID前缀）
+// 子任务使用"父任务ID.序号"，如 3.2 或 3c.2
+fn parse_task_id(id_str: &str) -> TaskKey {
+    if let Some((parent_str, index_str)) = id_str.split_once('.') {
+        let parent = parent_str.strip_suffix('c').unwrap_or(parent_str).parse::<usize>();
+        return match (parent, index_str.parse::<usize>()) {
+            (Ok(parent), Ok(index)) if parent > 0 => TaskKey::Child { parent, index },
+            _ => TaskKey::Invalid,
+        };
+    }
+
+    if let Some(num_str) = id_str.strip_suffix('c') {
+        if let Ok(id) = num_str.parse::<usize>() {
+            return TaskKey::Display { id, completed: true };
+        }
+    }
+
+    if let Ok(id) = id_str.parse::<usize>() {
+        return TaskKey::Display { id, completed: false };
+    }
+
+    // 尝试作为UID解析
+    let uid_str = id_str.strip_prefix('@').unwrap_or(id_str).to_lowercase();
+    let hex_len = uid_str.chars().filter(|c| c.is_ascii_hexdigit()).count();
+    let is_uid_like = uid_str.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+
+    if is_uid_like && hex_len >= MIN_UID_PREFIX_LEN && uid_str.len() <= 36 {
+        return TaskKey::Uid(uid_str);
+    }
+
+    TaskKey::Invalid // 解析失败
+}
+
+// 解析 +tag / -tag，返回 (是否添加, 小写标签名)
+fn parse_tag(word: &str) -> Option<(bool, String)> {
+    let (adding, name) = if let Some(name) = word.strip_prefix('+') {
+        (true, name)
+    } else if let Some(name) = word.strip_prefix('-') {
+        (false, name)
+    } else {
+        return None;
+    };
+
+    let valid = !name.is_empty()
+        && !name.starts_with(['+', '-'])
+        && name.chars().all(|c| !c.is_whitespace() && c != ',');
+    valid.then(|| (adding, name.to_lowercase()))
+}
+
+// tag 命令的参数：+tag 添加，-tag 移除
+fn parse_tag_change(word: &str) -> Result<(bool, String), String> {
+    parse_tag(word).ok_or_else(|| get_translations().invalid_tag(word))
+}
+
+// 从任务内容中提取内联的 +tag，返回去掉标签后的描述和标签列表
+// 只有 + 后面是字母（或 @ 加字母的上下文）的词才是标签，如 "bump to +1" 中的 +1 不是标签
+// 标签连同它前面的空白一起去掉，其余文本（包括多个空格和换行）保持原样
+fn extract_tags(input: &str) -> (String, Vec<String>) {
+    let mut description = String::new();
+    let mut tags: Vec<String> = Vec::new();
+    let mut last_was_tag = false;
+    let mut rest = input;
+
+    loop {
+        let (space, after) = rest.split_at(rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()));
+        let (word, remaining) = after.split_at(after.find(char::is_whitespace).unwrap_or(after.len()));
+        if word.is_empty() {
+            // 末尾的空白随最后的标签一起去掉
+            if !last_was_tag {
+                description.push_str(space);
+            }
+            break;
+        }
+
+        match inline_tag(word) {
+            Some(tag) => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                last_was_tag = true;
+            },
+            None => {
+                // 位于开头的标签之后的空白也去掉
+                if !(last_was_tag && description.is_empty()) {
+                    description.push_str(space);
+                }
+                description.push_str(word);
+                last_was_tag = false;
+            },
+        }
+        rest = remaining;
+    }
+
+    // 整行都是标签时保留原文作为描述，避免出现空任务
+    if description.trim().is_empty() {
+        return (input.trim().to_string(), Vec::new());
+    }
+    (description, tags)
+}
+
+// 内联标签：+ 后面以字母开头，或以 @ 加字母开头（上下文）
+fn inline_tag(word: &str) -> Option<String> {
+    let (true, tag) = parse_tag(word)? else {
+        return None;
+    };
+    let name = tag.strip_prefix('@').unwrap_or(&tag);
+    name.starts_with(char::is_alphabetic).then_some(tag)
+}
+
+// 格式化标签列表用于显示
+fn format_tags(tags: &[String]) -> ColoredString {
+    tags.iter()
+        .map(|tag| format!("+{}", tag))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .cyan()
+}
+
+// 过滤表达式中的单个条件
+#[derive(Debug, Clone)]
+enum Condition {
+    Tag(String),                                 // +tag
+    Due(Comparison, DateTime<Local>),            // due:<fri
+    NoDue,                                       // due:none
+    Created(Comparison, DateTime<Local>),        // created:>=2026-10-01
+    Done(Comparison, DateTime<Local>),           // done:>=last-week，未完成的任务不匹配
+    Priority(Priority),                          // priority:h
+    Overdue,
+    Starred,
+    Completed,
+    Pending,
+    Text(String),                                // 其他词语：在描述中搜索（不区分大小写）
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Before,
+    AtOrBefore,
+    On,
+    AtOrAfter,
+    After,
+}
+
+impl Comparison {
+    fn accepts(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Comparison::Before => ordering == Less,
+            Comparison::AtOrBefore => ordering != Greater,
+            Comparison::On => ordering == Equal,
+            Comparison::AtOrAfter => ordering != Less,
+            Comparison::After => ordering == Greater,
+        }
+    }
+}
+
+// 比较任务时间与过滤条件中的时间，条件只有日期时按日期比较
+fn compare_to(date: &DateTime<Local>, value: &DateTime<Local>) -> std::cmp::Ordering {
+    if has_due_time(value) {
+        date.cmp(value)
+    } else {
+        date.date_naive().cmp(&value.date_naive())
+    }
+}
+
+impl Condition {
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            Condition::Tag(tag) => task.tags.contains(tag),
+            Condition::Due(cmp, value) => task.due_date.is_some_and(|due| cmp.accepts(compare_to(&due, value))),
+            Condition::NoDue => task.due_date.is_none(),
+            Condition::Created(cmp, value) => cmp.accepts(compare_to(&task.created_at, value)),
+            Condition::Done(cmp, value) => task.completed_at.is_some_and(|done| cmp.accepts(compare_to(&done, value))),
+            Condition::Priority(priority) => task.priority == *priority,
+            Condition::Overdue => !task.completed && matches!(get_due_status(task.due_date.as_ref()), DueStatus::Overdue),
+            Condition::Starred => task.priority == Priority::High,
+            Condition::Completed => task.completed,
+            Condition::Pending => !task.completed,
+            Condition::Text(text) => task.description.to_lowercase().contains(text),
+            Condition::Not(condition) => !condition.matches(task),
+            Condition::And(conditions) => conditions.iter().all(|c| c.matches(task)),
+            Condition::Or(conditions) => conditions.iter().any(|c| c.matches(task)),
+        }
+    }
+}
+
+// 需要取值的过滤选项，值可能包含空格（如 --due-before "next monday"）
+const FILTER_VALUE_OPTIONS: [&str; 5] = ["--due-before", "--due-after", "--created-since", "--since", "--search"];
+
+// 任务列表的过滤条件，所有条件都满足时才显示
+#[derive(Debug, Default, Clone)]
+struct TaskFilter {
+    conditions: Vec<Condition>,
+}
+
+impl TaskFilter {
+    // 从命令行参数解析过滤表达式，如 ["+backend", "-blocked"] 或 ["due:<fri and +work and not starred"]
+    // 条件之间默认为 and，支持 or、not 和括号
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if FILTER_VALUE_OPTIONS.contains(&arg.as_str()) {
+                tokens.push(arg.clone());
+                tokens.extend(args.next().cloned());
+                continue;
+            }
+            for word in arg.split_whitespace() {
+                tokens.extend(split_parens(word));
+            }
+        }
+
+        let mut parser = FilterParser { tokens, pos: 0 };
+        let mut filter = TaskFilter::default();
+        if !parser.tokens.is_empty() {
+            filter.conditions.push(parser.parse_or()?);
+            if let Some(token) = parser.peek() {
+                return Err(get_translations().invalid_filter(token));
+            }
+        }
+        Ok(filter)
+    }
+
+    // 添加一个由 --due-before 等选项给出的条件
+    fn add_option(&mut self, option: &str, value: Option<&str>) -> Result<(), String> {
+        self.conditions.push(option_condition(option, value)?);
+        Ok(())
+    }
+
+    fn matches(&self, task: &Task) -> bool {
+        self.conditions.iter().all(|c| c.matches(task))
+    }
+}
+
+// 将词语两端的括号拆成单独的记号，如 "(+work" -> ["(", "+work"]
+fn split_parens(word: &str) -> Vec<String> {
+    let core = word.trim_start_matches('(');
+    let mut tokens = vec!["(".to_string(); word.len() - core.len()];
+    let inner = core.trim_end_matches(')');
+    if !inner.is_empty() {
+        tokens.push(inner.to_string());
+    }
+    tokens.extend(std::iter::repeat_n(")".to_string(), core.len() - inner.len()));
+    tokens
+}
+
+// 过滤表达式的递归下降解析器
+struct FilterParser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl FilterParser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|s| s.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // or_expr := and_expr ("or" and_expr)*
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.parse_and()?];
+        while self.peek().is_some_and(|t| t.eq_ignore_ascii_case("or")) {
+            self.pos += 1;
+            conditions.push(self.parse_and()?);
+        }
+        Ok(if conditions.len() == 1 { conditions.remove(0) } else { Condition::Or(conditions) })
+    }
+
+    // and_expr := unary (["and"] unary)*，相邻的条件默认为 and
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(t) if t.eq_ignore_ascii_case("and") => self.pos += 1,
+                Some(t) if t.eq_ignore_ascii_case("or") || t == ")" => break,
+                Some(_) => {},
+                None => break,
+            }
+            conditions.push(self.parse_unary()?);
+        }
+        Ok(if conditions.len() == 1 { conditions.remove(0) } else { Condition::And(conditions) })
+    }
+
+    // unary := "not" unary | "(" or_expr ")" | atom
+    fn parse_unary(&mut self) -> Result<Condition, String> {
+        let t = get_translations();
+        let token = self.next().ok_or_else(|| t.incomplete_filter())?;
+
+        if token.eq_ignore_ascii_case("not") {
+            return Ok(Condition::Not(Box::new(self.parse_unary()?)));
+        }
+        if token == "(" {
+            let condition = self.parse_or()?;
+            return match self.next() {
+                Some(token) if token == ")" => Ok(condition),
+                _ => Err(t.incomplete_filter()),
+            };
+        }
+        if FILTER_VALUE_OPTIONS.contains(&token.as_str()) {
+            let value = self.next();
+            return option_condition(&token, value.as_deref());
+        }
+        parse_condition(&token)
+    }
+}
 
-    /// 标记任务为未完成
-    #[clap(short = 'u', long = "undo", value_name = "ID", conflicts_with_all = &["complete_id", "edit_id", "task"])]
-    undo_id: Option<String>,
+// 解析单个条件，如 +work、-blocked、due:<fri、created:>=2026-10-01、starred
+fn parse_condition(token: &str) -> Result<Condition, String> {
+    let t = get_translations();
+    let invalid = || t.invalid_filter(token);
+    let lower = token.to_lowercase();
 
-    /// 将任务标记为重要（置顶）
-    #[clap(long = "star", value_name = "ID", conflicts_with_all = &["complete_id", "undo_id", "edit_id", "task"])]
-    star_id: Option<String>,
+    match lower.as_str() {
+        "starred" | "--starred" => return Ok(Condition::Starred),
+        "completed" | "--completed" => return Ok(Condition::Completed),
+        "pending" => return Ok(Condition::Pending),
+        "overdue" | "--overdue" => return Ok(Condition::Overdue),
+        "due:none" => return Ok(Condition::NoDue),
+        "due:any" => return Ok(Condition::Not(Box::new(Condition::NoDue))),
+        _ => {}
+    }
 
-    /// 取消任务的重要标记
-    #[clap(long = "unstar", value_name = "ID", conflicts_with_all = &["star_id", "complete_id", "undo_id", "edit_id", "task"])]
-    unstar_id: Option<String>,
+    if token.starts_with('+') || (token.starts_with('-') && !token.starts_with("--")) {
+        return match parse_tag(token) {
+            Some((true, tag)) => Ok(Condition::Tag(tag)),
+            Some((false, tag)) => Ok(Condition::Not(Box::new(Condition::Tag(tag)))),
+            None => Err(t.invalid_tag(token)),
+        };
+    }
 
-    /// 删除任务 (可多个ID，空格分隔)
-    #[clap(short = 'd', long = "delete", num_args = 1.., value_name = "ID", conflicts_with_all = &["edit_id", "complete_id", "undo_id", "star_id", "unstar_id", "task"])]
-    delete_ids: Vec<String>,
+    if let Some((field, value)) = lower.split_once(':') {
+        let (cmp, value) = if let Some(v) = value.strip_prefix("<=") {
+            (Comparison::AtOrBefore, v)
+        } else if let Some(v) = value.strip_prefix(">=") {
+            (Comparison::AtOrAfter, v)
+        } else if let Some(v) = value.strip_prefix('<') {
+            (Comparison::Before, v)
+        } else if let Some(v) = value.strip_prefix('>') {
+            (Comparison::After, v)
+        } else {
+            (Comparison::On, value)
+        };
 
-    /// 列出所有任务
-    #[clap(short = 'l', long = "list", conflicts_with_all = &["edit_id", "complete_id", "delete_ids", "task"])]
-    list: bool,
+        return match field {
+            "due" => Ok(Condition::Due(cmp, parse_date(value).map_err(|_| invalid())?)),
+            "created" => Ok(Condition::Created(cmp, parse_date(value).map_err(|_| invalid())?)),
+            "done" => Ok(Condition::Done(cmp, parse_date(value).map_err(|_| invalid())?)),
+            "priority" => Ok(Condition::Priority(parse_priority(value).map_err(|_| invalid())?)),
+            _ => Err(invalid()),
+        };
+    }
 
-    /// 显示任务的详细信息
-    #[clap(long = "show", value_name = "ID", conflicts_with_all = &["edit_id", "complete_id", "undo_id", "star_id", "unstar_id", "delete_ids", "task"])]
-    show_id: Option<String>,
+    // 过滤表达式开始后的参数都作为表达式的一部分，写在其后的其他选项无法识别
+    if token.starts_with("--") {
+        return Err(t.option_in_filter(token));
+    }
+    Ok(Condition::Text(lower))
+}
 
-    /// 启用批量添加任务模式
-    #[clap(short = 'm', long = "multi", conflicts_with_all = &["edit_id", "complete_id", "undo_id", "star_id", "unstar_id", "delete_ids", "task", "show_id"])]
-    multi_mode: bool,
+// 由 --due-before、--due-after、--created-since、--since、--search 等选项生成条件
+fn option_condition(option: &str, value: Option<&str>) -> Result<Condition, String> {
+    let t = get_translations();
+    let value = value.ok_or_else(|| t.incomplete_filter())?;
+    let date = || parse_date(value).map_err(|e| e.to_string());
 
-    #[clap(subcommand)]
-    command: Option<Commands>,
+    match option {
+        "--due-before" => Ok(Condition::Due(Comparison::Before, date()?)),
+        "--due-after" => Ok(Condition::Due(Comparison::After, date()?)),
+        "--created-since" => Ok(Condition::Created(Comparison::AtOrAfter, date()?)),
+        "--since" => Ok(Condition::Done(Comparison::AtOrAfter, date()?)),
+        "--search" => Ok(Condition::Text(value.to_lowercase())),
+        _ => Err(t.invalid_filter(option)),
+    }
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    /// 列出所有任务
-    List,
-    /// 完成一个任务
-    Done {
-        /// 任务ID
-        id: String,
-    },
-    /// 将完成的任务标记为未完成
-    Undo {
-        /// 任务ID
-        id: String,
-    },
-    /// 删除一个任务
-    Remove {
-        /// 任务ID
-        id: String,
-    },
-    /// 编辑一个任务
-    Edit {
-        /// 任务ID
-        id: String,
-        /// 新的任务内容
-        #[clap(required_unless_present = "time")]
-        content: Option<String>,
-        /// 设置任务截止日期 (格式: YYYY-MM-DD)
-        #[clap(short = 't', long = "time")]
-        time: Option<String>,
-    },
-    /// 标记任务为重要（置顶）
-    Star {
-        /// 任务ID
-        id: String,
-    },
-    /// 取消任务重要标记
-    Unstar {
-        /// 任务ID
-        id: String,
-    },
-    /// 显示任务的详细信息
-    Show {
-        /// 任务ID
-        id: String,
-    },
-    /// 显示帮助信息
-    Help,
+// 日程视图
+#[derive(Debug, Clone, Copy)]
+enum AgendaView {
+    Today,         // 今天到期及已过期
+    Week,          // 本周日之前到期及已过期
+    Overdue,       // 已过期
+    Upcoming(u32), // 从今天起N天内到期
 }
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Task {
-    id: usize,
-    description: String,
-    completed: bool,
-    created_at: DateTime<Local>,
-    due_date: Option<DateTime<Local>>,
-    starred: bool,    
-    deleted: bool,    
+
+impl AgendaView {
+    fn includes(self, due: &DateTime<Local>, today: NaiveDate) -> bool {
+        let day = due.date_naive();
+        match self {
+            AgendaView::Today => day <= today,
+            AgendaView::Week => {
+                let days_to_sunday = 6 - today.weekday().num_days_from_monday();
+                day <= today + chrono::Duration::days(days_to_sunday.into())
+            },
+            AgendaView::Overdue => matches!(get_due_status(Some(due)), DueStatus::Overdue),
+            // 天数过大超出日期范围时没有上限
+            AgendaView::Upcoming(days) => day >= today
+                && today.checked_add_days(Days::new(days.into())).is_none_or(|end| day < end),
+        }
+    }
+
+    fn title(self, t: &Translations) -> String {
+        match self {
+            AgendaView::Today => t.view_today(),
+            AgendaView::Week => t.view_week(),
+            AgendaView::Overdue => t.view_overdue(),
+            AgendaView::Upcoming(days) => t.view_upcoming(days),
+        }
+    }
 }
-#[derive(Debug, Serialize, Deserialize)]
-struct TodoList {
-    tasks: Vec<Task>,
-    file_path: PathBuf,   
-    next_id: usize,      
+
+// 搜索方式
+enum Matcher {
+    Substring(Vec<char>),  // 不区分大小写的子串
+    Regex(Regex),          // 不区分大小写的正则表达式
+    Fuzzy(Vec<char>),      // 按顺序出现的字符
 }
 
-// 辅助函数：解析任务ID（支持后缀'c'表示已完成任务）
-fn parse_task_id(id_str: &str) -> (usize, bool) {
-    if id_str.ends_with('c') {
-        if let Ok(id) = id_str[0..id_str.len()-1].parse::<usize>() {
-            return (id, true); // 返回ID和"是否完成"标志       
+impl Matcher {
+    fn new(pattern: &str, regex: bool, fuzzy: bool) -> Result<Self, String> {
+        if regex {
+            return RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| get_translations().invalid_regex(&e.to_string()));
         }
+        let chars = pattern.chars().flat_map(char::to_lowercase).collect();
+        Ok(if fuzzy { Matcher::Fuzzy(chars) } else { Matcher::Substring(chars) })
     }
 
-    if let Ok(id) = id_str.parse::<usize>() {
-        return (id, false);        
+    // 返回 (得分, 匹配的字节范围)，得分越小越好；只有模糊匹配会给出非零得分
+    fn find(&self, text: &str) -> Option<(usize, Vec<std::ops::Range<usize>>)> {
+        // 小写后的字符及其在原文中的字节范围
+        let chars: Vec<(std::ops::Range<usize>, char)> = text.char_indices()
+            .map(|(i, c)| (i..i + c.len_utf8(), c.to_lowercase().next().unwrap_or(c)))
+            .collect();
+
+        match self {
+            Matcher::Regex(regex) => {
+                let ranges: Vec<_> = regex.find_iter(text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| m.range())
+                    .collect();
+                (!ranges.is_empty()).then_some((0, ranges))
+            },
+            Matcher::Substring(pattern) => {
+                if pattern.is_empty() {
+                    return Some((0, Vec::new()));
+                }
+                let mut ranges = Vec::new();
+                let mut start = 0;
+                while start + pattern.len() <= chars.len() {
+                    if chars[start..start + pattern.len()].iter().map(|(_, c)| c).eq(pattern.iter()) {
+                        ranges.push(chars[start].0.start..chars[start + pattern.len() - 1].0.end);
+                        start += pattern.len();
+                    } else {
+                        start += 1;
+                    }
+                }
+                (!ranges.is_empty()).then_some((0, ranges))
+            },
+            Matcher::Fuzzy(pattern) => {
+                let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+                let mut positions = Vec::new();
+                let mut wanted = pattern.iter().peekable();
+                for (index, (range, c)) in chars.iter().enumerate() {
+                    if wanted.peek() == Some(&c) {
+                        wanted.next();
+                        positions.push(index);
+                        match ranges.last_mut() {
+                            Some(last) if last.end == range.start => last.end = range.end,
+                            _ => ranges.push(range.clone()),
+                        }
+                    }
+                }
+                if wanted.peek().is_some() {
+                    return None;
+                }
+                // 匹配的字符越集中得分越好
+                let span = match (positions.first(), positions.last()) {
+                    (Some(first), Some(last)) => last - first + 1 - positions.len(),
+                    _ => 0,
+                };
+                Some((span, ranges))
+            },
+        }
     }
+}
 
-    (0, false) // 解析失败    
+// 高亮显示匹配的部分
+fn highlight(text: &str, ranges: &[std::ops::Range<usize>]) -> String {
+    let mut result = String::new();
+    let mut last = 0;
+    for range in ranges {
+        result.push_str(&text[last..range.start]);
+        result.push_str(&text[range.clone()].black().on_yellow().to_string());
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    result
 }
 
 // 截断字符串，同时考虑显示宽度
@@ -873,7 +3886,8 @@ fn fixed_width_string(s: &str, width: usize) -> String {
     }    
 }
 
-// 截止日期临近程度的枚举
+// 截止日期临近程度的枚举，声明顺序即按紧急程度排序的顺序
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
 enum DueStatus {
     Overdue,    // 已过期
     Urgent,     // 紧急 (1天内) 
@@ -886,27 +3900,65 @@ enum DueStatus {
 fn get_due_status(due_date: Option<&DateTime<Local>>) -> DueStatus {
     if let Some(date) = due_date {
         let now = Local::now();
-        let days_remaining = (*date - now).num_days();
-        
-        if days_remaining < 0 {
-            DueStatus::Overdue
-        } else if days_remaining == 0 {
-            DueStatus::Urgent
-        } else if days_remaining <= 3 {
-            DueStatus::Soon
-        } else { 
-            DueStatus::Normal
+
+        if has_due_time(date) {
+            // 带时间的截止日期按小时计算，24小时内到期视为紧急
+            let hours_remaining = (*date - now).num_minutes() as f64 / 60.0;
+            if hours_remaining < 0.0 {
+                DueStatus::Overdue
+            } else if hours_remaining < 24.0 {
+                DueStatus::Urgent
+            } else if hours_remaining <= 72.0 {
+                DueStatus::Soon
+            } else {
+                DueStatus::Normal
+            }
+        } else {
+            // 只有日期时截止到当天结束，按日历日计算
+            let days_remaining = (date.date_naive() - now.date_naive()).num_days();
+            if days_remaining < 0 {
+                DueStatus::Overdue
+            } else if days_remaining == 0 {
+                DueStatus::Urgent
+            } else if days_remaining <= 3 {
+                DueStatus::Soon
+            } else { 
+                DueStatus::Normal
+            }
         }
     } else { 
         DueStatus::NoDue       
     }    
 }
 
+// 截止日期是否指定了具体时间（零点表示只有日期）
+// 子任务完成进度，如 " [2/5]"，没有子任务时为空
+fn subtask_progress(subtasks: &[&Task]) -> String {
+    if subtasks.is_empty() {
+        return String::new();
+    }
+    let done = subtasks.iter().filter(|task| task.completed).count();
+    format!(" [{}/{}]", done, subtasks.len())
+}
+
+fn has_due_time(date: &DateTime<Local>) -> bool {
+    date.time() != NaiveTime::MIN
+}
+
+// 格式化截止日期，指定了时间时一并显示
+fn format_due(date: &DateTime<Local>) -> String {
+    if has_due_time(date) {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    } else {
+        date.format("%Y-%m-%d").to_string()
+    }
+}
+
 // 根据截止日期状态返回颜色化的日期字符串
 fn format_due_date(date: Option<&DateTime<Local>>, t: &Translations) -> ColoredString {
     match date {
         Some(date) => {
-            let formatted = date.format("%Y-%m-%d").to_string();
+            let formatted = format_due(date);
             match get_due_status(Some(date)) {
                 DueStatus::Overdue => formatted.red().bold(),
                 DueStatus::Urgent => formatted.bright_red(),
@@ -920,67 +3972,91 @@ fn format_due_date(date: Option<&DateTime<Local>>, t: &Translations) -> ColoredS
 }
 
 impl TodoList {
-    fn new() -> Result<Self, io::Error> {
-        let mut file_path = dirs::home_dir().unwrap_or_default();
-        file_path.push(".jodo");
-        file_path.push("tasks.json");
+    fn new(list_name: &str) -> Result<Self, io::Error> {
+        let file_path = get_list_path(list_name);
         
         // 确保目录存在
         if let Some(parent) = file_path.parent() {
             fs::create_dir_all(parent)?;    
         }
         
-        let tasks: Vec<Task> = if file_path.exists() {
-            let mut file = File::open(&file_path)?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)?;
-            serde_json::from_str(&contents).unwrap_or_default()
-        } else {
-            Vec::new()        
-        };
+        // 整个加载-保存过程都持有锁，避免与其他jodo进程交错写入
+        let _lock = lock_tasks_file(&file_path)?;
+        let (tasks, contents) = read_tasks_file(&file_path)?;
     
         // 创建TodoList实例
         let mut todo_list = Self { 
             tasks,
             file_path,  
             next_id: 1,  // 临时值，会在reassign_ids中更新        
+            last_synced: contents,
+            last_changes: Vec::new(),
         };
     
         // 程序启动时就重新分配ID，确保任务总是从1开始连续编号
         todo_list.reassign_ids();    
-        todo_list.save()?; 
+        todo_list.save_locked(None)?; 
     
         Ok(todo_list)        
     }
-    fn add_task(&mut self, description: String, due_date: Option<DateTime<Local>>) -> Result<(), io::Error> {
+
+    // 如果其他进程修改了任务文件，则重新读取（仅用于不依赖显示ID的操作，如添加任务）
+    fn reload_if_changed(&mut self) -> Result<(), io::Error> {
+        let (tasks, contents) = read_tasks_file(&self.file_path)?;
+        if contents != self.last_synced {
+            self.tasks = tasks;
+            self.last_synced = contents;
+            self.reassign_ids();
+        }
+        Ok(())
+    }
+
+    fn add_task(&mut self, description: String, due_date: Option<DateTime<Local>>, tags: Vec<String>, priority: Priority, repeat: Option<Repeat>, parent: Option<Uuid>) -> Result<(), io::Error> {
+        // 添加任务不依赖已显示的ID，可以安全地合并其他进程的修改（例如批量模式期间）
+        let _lock = lock_tasks_file(&self.file_path)?;
+        self.reload_if_changed()?;
+
         let task = Task {
             id: self.next_id, // 临时ID 
+            uid: Uuid::new_v4(),
             description,
             completed: false,
             created_at: Local::now(),
             due_date,
-            starred: false,  
+            local_due: true,
+            starred: priority == Priority::High,
             deleted: false,        
+            deleted_at: None,
+            tags,
+            priority,
+            repeat,
+            parent,
+            parent_id: 0,
+            depends_on: Vec::new(),
+            notes: String::new(),
+            completed_at: None,
         };
     
         self.tasks.push(task);
         self.reassign_ids(); // 重新分配所有ID   
-        self.save()        
+        self.save_locked(Some(Operation::Add))        
     }
 
     // 列出任务
-    fn list_tasks(&self) {
+    fn list_tasks(&self, filter: &TaskFilter, sort: &[SortKey]) {
         let t = get_translations();
     
-        // 过滤出未删除的任务    
-        let filtered_tasks: Vec<&Task> = self.tasks.iter().filter(|task| !task.deleted).collect();
+        // 过滤出未删除且符合条件的顶层任务，子任务显示在父任务下方
+        let filtered_tasks: Vec<&Task> = self.tasks.iter()
+            .filter(|task| !task.deleted && task.parent_id == 0 && filter.matches(task))
+            .collect();
     
         if filtered_tasks.is_empty() {
             println!("{}", t.no_tasks());   
             return;        
         }
     
-        // 按照完成状态、星标和ID排序
+        // 按照完成状态、优先级和ID排序
         let mut incomplete_tasks: Vec<&Task> = filtered_tasks.iter()
             .filter(|t| !t.completed) 
             .cloned()        
@@ -991,17 +4067,16 @@ impl TodoList {
             .cloned()        
             .collect();
     
-        // 排序逻辑：首先按照星标排序，然后严格按照ID数值排序
-        incomplete_tasks.sort_by(|a, b| {
-            match (a.starred, b.starred) {
-                (true, false) => Ordering::Less,
-                (false, true) => Ordering::Greater,   
-                _ => a.id.cmp(&b.id)  // 按ID升序排序     
-            }        
-        });
-    
-        // 已完成任务严格按ID排序    
-        completed_tasks.sort_by_key(|t| t.id);
+        if sort.is_empty() {
+            // 默认排序：未完成任务首先按照优先级排序，然后严格按照ID数值排序
+            incomplete_tasks.sort_by_key(|t| (t.priority, t.id));
+        
+            // 已完成任务严格按ID排序    
+            completed_tasks.sort_by_key(|t| t.id);
+        } else {
+            sort_tasks(&mut incomplete_tasks, sort);
+            sort_tasks(&mut completed_tasks, sort);
+        }
     
         println!("{}",t.incomplete_tasks());
         if incomplete_tasks.is_empty() {
@@ -1011,22 +4086,34 @@ impl TodoList {
             println!("{}", "-".repeat(70));
     
             for task in &incomplete_tasks {
-                let star_marker = if task.starred { "★ ".yellow() } else { "  ".into() };
+                let priority_marker = task.priority.marker();
                 // 使用新的格式化函数显示彩色的截止日期
                 let due_date = format_due_date(task.due_date.as_ref(), &t);
                 
-                // 根据可用空间计算描述的最大长度，考虑中文字符
-                let max_desc_width = 36; // 留4个字符的余量
+                // 根据可用空间计算描述的最大长度，考虑中文字符，有子任务时在描述后显示进度
+                let subtasks = self.subtasks(task);
+                // 有备注的任务在描述后显示 *
+                let note_marker = if task.notes.is_empty() { "" } else { " *" };
+                let progress = format!("{}{}", subtask_progress(&subtasks), note_marker);
+                let max_desc_width = 36 - display_width(&progress); // 留4个字符的余量
                 let truncated_desc = truncate_str(&task.description, max_desc_width);            
-                let formatted_desc = fixed_width_string(&truncated_desc, 40);
+                let formatted_desc = fixed_width_string(&format!("{}{}", truncated_desc, progress), 40);
     
-                println!(
-                    "{}{:<5} {} {:<15}",
-                    star_marker,
-                    task.id.to_string().blue(),
-                    formatted_desc,  
-                    due_date       
-                );       
+                // 依赖的任务尚未完成时，整行以灰色显示
+                if !self.open_dependencies(task).is_empty() {
+                    let line = format!("{}{:<5} {} {:<15} {}", priority_marker.clear(), task.id, formatted_desc, due_date.clear(), format_tags(&task.tags).clear());
+                    println!("{}", line.dimmed());
+                } else {
+                    println!(
+                        "{}{:<5} {} {:<15} {}",
+                        priority_marker,
+                        task.id.to_string().blue(),
+                        formatted_desc,  
+                        due_date,
+                        format_tags(&task.tags)
+                    );       
+                }
+                self.print_subtasks(&subtasks, &t);
             }        
         }
     
@@ -1042,32 +4129,78 @@ impl TodoList {
                 
                 // 已完成任务的截止日期不需要特殊颜色标记
                 let due_date = match task.due_date {
-                    Some(date) => date.format("%Y-%m-%d").to_string().normal(),  
+                    Some(date) => format_due(&date).normal(),  
                     None => t.none().normal(),    
                 };
                 
                 // 对已完成任务也处理中文显示问题
-                let max_desc_width = 36;
+                let subtasks = self.subtasks(task);
+                // 有备注的任务在描述后显示 *
+                let note_marker = if task.notes.is_empty() { "" } else { " *" };
+                let progress = format!("{}{}", subtask_progress(&subtasks), note_marker);
+                let max_desc_width = 36 - display_width(&progress);
                 let truncated_desc = truncate_str(&task.description, max_desc_width);
-                let formatted_desc = fixed_width_string(&truncated_desc, 40);
+                let formatted_desc = fixed_width_string(&format!("{}{}", truncated_desc, progress), 40);
                 
                 println!(
-                    "{:<5} {} {:<15}",
+                    "{:<5} {} {:<15} {}",
                     task_id.green(),
                     formatted_desc,  
-                    due_date       
+                    due_date,
+                    format_tags(&task.tags)
                 );       
+                self.print_subtasks(&subtasks, &t);
             }       
         }        
     }
 
+    // 任务的子任务（未删除），按子任务序号排列
+    fn subtasks(&self, task: &Task) -> Vec<&Task> {
+        let mut subtasks: Vec<&Task> = self.tasks.iter()
+            .filter(|sub| !sub.deleted && sub.parent_id > 0 && sub.parent == Some(task.uid))
+            .collect();
+        subtasks.sort_by_key(|sub| sub.id);
+        subtasks
+    }
+
+    // 在父任务下方缩进显示子任务，已完成或依赖的任务尚未完成的子任务以灰色显示
+    fn print_subtasks(&self, subtasks: &[&Task], t: &Translations) {
+        for task in subtasks {
+            let check = if task.completed { "[x]" } else { "[ ]" };
+            let note_marker = if task.notes.is_empty() { "" } else { " *" };
+            let desc = format!("  {} {}{}", check, truncate_str(&task.description, 30), note_marker);
+            let formatted_desc = fixed_width_string(&desc, 40);
+            let due_date = match &task.due_date {
+                Some(date) if !task.completed => format_due_date(Some(date), t),
+                Some(date) => format_due(date).normal(),
+                None => "".normal(),
+            };
+
+            if task.completed {
+                let line = format!("  {:<5} {} {:<15} {}", task.display_id(), formatted_desc, due_date, format_tags(&task.tags).clear());
+                println!("{}", line.dimmed());
+            } else if !self.open_dependencies(task).is_empty() {
+                let line = format!("{}{:<5} {} {:<15} {}", task.priority.marker().clear(), task.display_id(), formatted_desc, due_date.clear(), format_tags(&task.tags).clear());
+                println!("{}", line.dimmed());
+            } else {
+                println!(
+                    "{}{:<5} {} {:<15} {}",
+                    task.priority.marker(),
+                    task.display_id().blue(),
+                    formatted_desc,
+                    due_date,
+                    format_tags(&task.tags)
+                );
+            }
+        }
+    }
+
     // 修改edit_task方法，移除group参数
-    fn edit_task(&mut self, id_str: &str, new_desc: Option<&str>, due_date: Option<DateTime<Local>>) -> Result<(), &'static str> {
-        let (id, is_completed) = parse_task_id(id_str);
+    fn edit_task(&mut self, id_str: &str, new_desc: Option<&str>, due_date: Option<DateTime<Local>>, priority: Option<Priority>, repeat: Option<Option<Repeat>>) -> Result<(), &'static str> {
+        let key = self.resolve_id(id_str)?;
     
         if let Some(task) = self.tasks.iter_mut()
-            .filter(|t| !t.deleted && t.completed == is_completed && t.id == id)        
-            .next() {
+            .find(|t| !t.deleted && key.matches(t, true)) {
     
             if let Some(desc) = new_desc {
                 task.description = desc.to_string();    
@@ -1076,104 +4209,379 @@ impl TodoList {
             if due_date.is_some() {
                 task.due_date = due_date;    
             }
+
+            // Some(None) 表示清除重复规则
+            if let Some(repeat) = repeat {
+                task.set_repeat(repeat);
+            }
+
+            if let Some(priority) = priority {
+                task.set_priority(priority);
+                self.reassign_ids(); // 优先级影响排序
+            }
             
-            self.save().map_err(|_| "保存任务失败")?;
+            self.save(Operation::Edit).map_err(save_error)?;
             Ok(())
         } else { 
             Err("任务不存在")       
         }        
     }
 
-    fn mark_done(&mut self, id_str: &str) -> Result<(), &'static str> {
+    // with_subtasks为true时同时完成该任务所有未完成的子任务
+    fn mark_done(&mut self, id_str: &str, with_subtasks: bool) -> Result<(), &'static str> {
         let t = get_translations();
-        let (id, _) = parse_task_id(id_str);
+        let key = self.resolve_id(id_str)?;
         
         if let Some(task) = self.tasks.iter_mut()
-            .find(|t| !t.deleted && !t.completed && t.id == id) {
+            .find(|t| !t.deleted && !t.completed && key.matches(t, false)) {
             task.completed = true;
+            task.completed_at = Some(Local::now());
+            let uid = task.uid;
+            self.spawn_next_occurrence(uid);
+
+            if with_subtasks {
+                let subtasks: Vec<Uuid> = self.tasks.iter()
+                    .filter(|sub| !sub.deleted && !sub.completed && sub.parent == Some(uid))
+                    .map(|sub| sub.uid)
+                    .collect();
+                for sub_uid in subtasks {
+                    if let Some(sub) = self.tasks.iter_mut().find(|sub| sub.uid == sub_uid) {
+                        sub.completed = true;
+                        sub.completed_at = Some(Local::now());
+                    }
+                    self.spawn_next_occurrence(sub_uid);
+                }
+            }
             self.reassign_ids(); // 重新分配ID
-            self.save().map_err(|_| t.save_failed())?;
+            self.save(Operation::Complete).map_err(save_error)?;
             Ok(())
         } else { 
             Err(t.task_already_completed())       
         }    
     }
     
+    // 完成重复任务时生成下一次任务，重复规则随之转移到新任务上，
+    // 这样重新打开并再次完成旧任务时不会重复生成
+    fn spawn_next_occurrence(&mut self, uid: Uuid) {
+        let Some(task) = self.tasks.iter_mut().find(|t| t.uid == uid) else {
+            return;
+        };
+        let Some(repeat) = task.repeat.take() else {
+            return;
+        };
+
+        let due = task.due_date.unwrap_or_else(Local::now);
+        let next_date = repeat.next_date_after(due.date_naive(), Local::now().date_naive());
+        let next_due = local_datetime(next_date, due.time());
+
+        let next = Task {
+            id: self.next_id, // 临时ID
+            uid: Uuid::new_v4(),
+            description: task.description.clone(),
+            completed: false,
+            created_at: Local::now(),
+            due_date: next_due,
+            local_due: true,
+            starred: task.starred,
+            deleted: false,
+            deleted_at: None,
+            tags: task.tags.clone(),
+            priority: task.priority,
+            repeat: Some(repeat),
+            parent: task.parent,
+            parent_id: 0,
+            depends_on: Vec::new(),
+            notes: String::new(),
+            completed_at: None,
+        };
+        self.next_id += 1;
+        self.tasks.push(next);
+    }
+
     fn mark_undone(&mut self, id_str: &str) -> Result<(), &'static str> {
         let t = get_translations();
-        let (id, _) = parse_task_id(id_str);
-        
+        let key = self.resolve_id(id_str)?;
+        
+        if let Some(task) = self.tasks.iter_mut()
+            .find(|t| !t.deleted && t.completed && key.matches(t, false)) {
+            task.completed = false;
+            task.completed_at = None;
+            self.reassign_ids(); // 重新分配ID
+            self.save(Operation::Reopen).map_err(save_error)?;
+            Ok(())
+        } else { 
+            Err(t.task_not_completed())       
+        }    
+    }
+    
+    fn star_task(&mut self, id_str: &str) -> Result<(), &'static str> {
+        let key = self.resolve_id(id_str)?;
+        
+        if let Some(task) = self.tasks.iter_mut()
+            .find(|t| !t.deleted && key.matches(t, true)) {
+            task.set_priority(Priority::High);
+            self.reassign_ids(); // 重新分配ID
+            self.save(Operation::Star).map_err(save_error)?;
+            Ok(())
+        } else { 
+            Err("任务不存在")       
+        }    
+    }
+    
+    fn unstar_task(&mut self, id_str: &str) -> Result<(), &'static str> {
+        let key = self.resolve_id(id_str)?;
+        
+        if let Some(task) = self.tasks.iter_mut()
+            .find(|t| !t.deleted && key.matches(t, true)) {
+            // 只撤销 --star 设置的高优先级，中、低优先级保持不变
+            if task.priority == Priority::High {
+                task.set_priority(Priority::None);
+            }
+            self.reassign_ids(); // 重新分配ID
+            self.save(Operation::Unstar).map_err(save_error)?;
+            Ok(())
+        } else { 
+            Err("任务不存在")       
+        }    
+    }
+    
+    // 修改任务标签，changes中的每一项为 +tag（添加）或 -tag（移除）
+    fn tag_task(&mut self, id_str: &str, changes: &[(bool, String)]) -> Result<(), String> {
+        let t = get_translations();
+        let key = self.resolve_id(id_str)?;
+
+        let (add, remove): (Vec<_>, Vec<_>) = changes.iter().cloned().partition(|(adding, _)| *adding);
+        let add: Vec<String> = add.into_iter().map(|(_, tag)| tag).collect();
+        let remove: Vec<String> = remove.into_iter().map(|(_, tag)| tag).collect();
+
+        if let Some(task) = self.tasks.iter_mut()
+            .find(|t| !t.deleted && key.matches(t, true)) {
+            task.tags.retain(|tag| !remove.contains(tag));
+            for tag in add {
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+            self.save(Operation::Tag).map_err(|e| save_error(e).to_string())?;
+            Ok(())
+        } else {
+            Err(t.task_not_exist().to_string())
+        }
+    }
+
+    // 应用在编辑器中对任务所做的修改，返回修改的任务数
+    // 编辑器可能打开很久，因此按UID查找并合并其他进程的修改
+    fn apply_edits(&mut self, edits: Vec<TaskEdit>) -> Result<usize, &'static str> {
+        let t = get_translations();
+        let _lock = lock_tasks_file(&self.file_path).map_err(save_error)?;
+        self.reload_if_changed().map_err(save_error)?;
+
+        if edits.iter().any(|edit| !self.tasks.iter().any(|task| task.uid == edit.uid && !task.deleted)) {
+            return Err(t.task_not_exist());
+        }
+
+        let count = edits.len();
+        for edit in edits {
+            let Some(task) = self.tasks.iter_mut().find(|task| task.uid == edit.uid) else {
+                continue;
+            };
+            if let Some(description) = edit.description {
+                task.description = description;
+            }
+            if let Some(due_date) = edit.due_date {
+                task.due_date = due_date;
+            }
+            if let Some(priority) = edit.priority {
+                task.set_priority(priority);
+            }
+            if let Some(repeat) = edit.repeat {
+                task.set_repeat(repeat);
+            }
+            if let Some(tags) = edit.tags {
+                task.tags = tags;
+            }
+            if let Some(notes) = edit.notes {
+                task.notes = notes;
+            }
+            match edit.completed {
+                Some(true) if !task.completed => {
+                    task.completed = true;
+                    task.completed_at = Some(Local::now());
+                    self.spawn_next_occurrence(edit.uid);
+                },
+                Some(false) => {
+                    task.completed = false;
+                    task.completed_at = None;
+                },
+                Some(true) => {},
+                None => {},
+            }
+        }
+
+        self.reassign_ids();
+        self.save_locked(Some(Operation::Edit)).map_err(save_error)?;
+        Ok(count)
+    }
+
+    // 设置任务备注；编辑器可能打开很久，因此按UID查找并合并其他进程的修改
+    fn set_notes(&mut self, uid: Uuid, notes: String) -> Result<(), &'static str> {
+        let t = get_translations();
+        let _lock = lock_tasks_file(&self.file_path).map_err(save_error)?;
+        self.reload_if_changed().map_err(save_error)?;
+
+        let task = self.tasks.iter_mut()
+            .find(|task| task.uid == uid && !task.deleted)
+            .ok_or(t.task_not_exist())?;
+        task.notes = notes;
+        self.save_locked(Some(Operation::Note)).map_err(save_error)
+    }
+
+    // 让任务依赖于其他任务，添加前检查是否会形成循环依赖
+    fn block_task(&mut self, id_str: &str, on: &[String]) -> Result<(), String> {
+        let t = get_translations();
+        let key = self.resolve_id(id_str)?;
+        let uid = self.tasks.iter()
+            .find(|task| !task.deleted && key.matches(task, true))
+            .map(|task| task.uid)
+            .ok_or_else(|| t.task_not_exist().to_string())?;
+
+        let mut dependencies = Vec::new();
+        for on_str in on {
+            let on_key = self.resolve_id(on_str)?;
+            let dependency = self.tasks.iter()
+                .find(|task| !task.deleted && on_key.matches(task, true))
+                .map(|task| task.uid)
+                .ok_or_else(|| t.task_not_exist().to_string())?;
+            if dependency == uid || self.depends_transitively(dependency, uid) {
+                return Err(t.dependency_cycle(id_str, on_str));
+            }
+            dependencies.push(dependency);
+        }
+
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.uid == uid) {
+            for dependency in dependencies {
+                if !task.depends_on.contains(&dependency) {
+                    task.depends_on.push(dependency);
+                }
+            }
+        }
+        self.save(Operation::Block).map_err(|e| save_error(e).to_string())?;
+        Ok(())
+    }
+
+    fn unblock_task(&mut self, id_str: &str, on: &[String]) -> Result<(), &'static str> {
+        let t = get_translations();
+        let dependencies: Vec<Uuid> = on.iter()
+            .map(|on_str| {
+                let on_key = self.resolve_id(on_str)?;
+                self.tasks.iter()
+                    .find(|task| !task.deleted && on_key.matches(task, true))
+                    .map(|task| task.uid)
+                    .ok_or(t.task_not_exist())
+            })
+            .collect::<Result<_, _>>()?;
+
+        let key = self.resolve_id(id_str)?;
         if let Some(task) = self.tasks.iter_mut()
-            .find(|t| !t.deleted && t.completed && t.id == id) {
-            task.completed = false;
-            self.reassign_ids(); // 重新分配ID
-            self.save().map_err(|_| t.save_failed())?;
+            .find(|task| !task.deleted && key.matches(task, true)) {
+            task.depends_on.retain(|uid| !dependencies.contains(uid));
+            self.save(Operation::Unblock).map_err(save_error)?;
             Ok(())
-        } else { 
-            Err(t.task_not_completed())       
-        }    
+        } else {
+            Err(t.task_not_exist())
+        }
     }
-    
-    fn star_task(&mut self, id_str: &str) -> Result<(), &'static str> {
-        let (id, is_completed) = parse_task_id(id_str);
-        
-        if let Some(task) = self.tasks.iter_mut()
-            .find(|t| !t.deleted && t.completed == is_completed && t.id == id) {
-            task.starred = true;
-            self.reassign_ids(); // 重新分配ID
-            self.save().map_err(|_| "保存任务失败")?;
-            Ok(())
-        } else { 
-            Err("任务不存在")       
-        }    
+
+    // 判断from是否直接或间接依赖于to
+    fn depends_transitively(&self, from: Uuid, to: Uuid) -> bool {
+        let mut pending = vec![from];
+        let mut visited = Vec::new();
+        while let Some(uid) = pending.pop() {
+            if visited.contains(&uid) {
+                continue;
+            }
+            visited.push(uid);
+            if let Some(task) = self.tasks.iter().find(|task| task.uid == uid) {
+                if task.depends_on.contains(&to) {
+                    return true;
+                }
+                pending.extend(task.depends_on.iter().copied());
+            }
+        }
+        false
     }
-    
-    fn unstar_task(&mut self, id_str: &str) -> Result<(), &'static str> {
-        let (id, is_completed) = parse_task_id(id_str);
-        
-        if let Some(task) = self.tasks.iter_mut()
-            .find(|t| !t.deleted && t.completed == is_completed && t.id == id) {
-            task.starred = false;
-            self.reassign_ids(); // 重新分配ID
-            self.save().map_err(|_| "保存任务失败")?;
-            Ok(())
-        } else { 
-            Err("任务不存在")       
-        }    
+
+    // 任务依赖的、尚未完成的任务（已删除的任务不再阻塞）
+    fn open_dependencies(&self, task: &Task) -> Vec<&Task> {
+        self.tasks.iter()
+            .filter(|dep| task.depends_on.contains(&dep.uid) && !dep.deleted && !dep.completed)
+            .collect()
     }
-    
-    fn remove_tasks(&mut self, id_strs: &[String]) -> Result<Vec<usize>, &'static str> {
+
+    // 完成任务前检查依赖，为仍有未完成依赖的任务生成警告
+    fn open_dependency_warnings(&self, id_strs: &[String]) -> Vec<String> {
+        let t = get_translations();
+        id_strs.iter()
+            .filter_map(|id_str| {
+                let key = self.resolve_id(id_str).ok()?;
+                let task = self.tasks.iter().find(|task| !task.deleted && !task.completed && key.matches(task, false))?;
+                let open = self.open_dependencies(task);
+                if open.is_empty() {
+                    return None;
+                }
+                let open = open.iter()
+                    .map(|dep| format!("{} {}", dep.display_id(), dep.description))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some(t.open_dependencies_warning(id_str, &open))
+            })
+            .collect()
+    }
+
+    fn remove_tasks(&mut self, id_strs: &[String]) -> Result<Vec<String>, &'static str> {
         // 首先将任务ID映射到内部任务索引，避免中途ID变化
         let mut task_indices_to_delete: Vec<(usize, usize, bool)> = Vec::new();  // (内部索引, 显示ID, 是否完成)    
         let mut display_ids = Vec::new();  // 用于显示的任务ID 
     
         // 第一步：收集所有要删除的任务信息
         for id_str in id_strs {    
-            let (display_id, is_completed) = parse_task_id(id_str);
+            let key = self.resolve_id(id_str)?;
     
             // 找到对应的任务在tasks数组中的实际索引位置
             if let Some((index, task)) = self.tasks.iter()
                 .enumerate()
-                .find(|(_, t)| !t.deleted && t.completed == is_completed && t.id == display_id) {
+                .find(|(_, t)| !t.deleted && key.matches(t, true)) {
                 
-                task_indices_to_delete.push((index, task.id, is_completed));
-                display_ids.push(task.id);
+                task_indices_to_delete.push((index, task.id, task.completed));
+                display_ids.push(task.display_id());
             } else { 
                 return Err("任务不存在");       
             }        
         }
     
         // 第二步：按索引标记删除，避免重排序导致的问题
+        let now = Local::now();
         for (index, _, _) in &task_indices_to_delete {
             self.tasks[*index].deleted = true;        
+            self.tasks[*index].deleted_at = Some(now);
+        }
+
+        // 子任务随父任务一起移入回收站，恢复父任务时一并恢复
+        let parents: Vec<Uuid> = task_indices_to_delete.iter()
+            .map(|(index, _, _)| self.tasks[*index].uid)
+            .collect();
+        for task in self.tasks.iter_mut()
+            .filter(|task| !task.deleted && task.parent.is_some_and(|uid| parents.contains(&uid))) {
+            task.deleted = true;
+            task.deleted_at = Some(now);
         }
     
         // 保存更改    
-        self.save().map_err(|_| "保存任务失败")?;
+        self.save(Operation::Delete).map_err(save_error)?;
     
         // 重新分配ID
         self.reassign_ids();    
-        self.save().map_err(|_| "保存任务失败")?;
+        self.save(Operation::Delete).map_err(save_error)?;
     
         Ok(display_ids)    
     }
@@ -1184,37 +4592,47 @@ impl TodoList {
         Ok(())        
     }
 
-    fn show_task_detail(&self, id_str: &str) -> Result<(), &'static str> {
+    fn show_task_detail(&self, id_str: &str, format: OutputFormat) -> Result<(), &'static str> {
         let t = get_translations();    
-        let (id, is_completed) = parse_task_id(id_str);
+        let key = self.resolve_id(id_str)?;
     
         if let Some(task) = self.tasks.iter()
-            .find(|t| !t.deleted && t.completed == is_completed && t.id == id) {
+            .find(|t| !t.deleted && key.matches(t, true)) {
             
+            if format != OutputFormat::Text {
+                print_tasks(&[task], format);
+                return Ok(());
+            }
+
             println!("{}", t.task_details());
             println!("{}", "=".repeat(50));
-            println!("{:<10}: {}", t.id(), if is_completed { 
-                format!("{}c", task.id).green()
+            println!("{:<10}: {}", t.id(), if task.completed { 
+                task.display_id().green()
             } else {
-                task.id.to_string().blue()
+                task.display_id().blue()
             });
+            println!("{:<10}: {}", t.uid(), task.uid.to_string().dimmed());
             println!("{:<10}: {}", t.status(), if task.completed { 
                 t.status_completed()
             } else { 
                 t.status_incomplete()
             });
-            println!("{:<10}: {}", t.starred(), if task.starred {
-                t.yes()
-            } else {
-                t.no()
-            });
+            println!("{:<10}: {}", t.priority(), t.priority_name(task.priority));
             println!("{:<10}: {}", t.created_at(), task.created_at.format("%Y-%m-%d %H:%M:%S"));
+            if let Some(completed_at) = task.completed_at {
+                println!("{:<10}: {}", t.completed_at(), completed_at.format("%Y-%m-%d %H:%M:%S"));
+            }
+            if task.tags.is_empty() {
+                println!("{:<10}: {}", t.tags(), t.none());
+            } else {
+                println!("{:<10}: {}", t.tags(), format_tags(&task.tags));
+            }
     
             // 在详细信息中也显示彩色截止日期
             let due_date_str = match &task.due_date {
                 Some(date) => {
                     if task.completed {
-                        date.format("%Y-%m-%d").to_string().normal()
+                        format_due(date).normal()
                     } else { 
                         format_due_date(Some(date), &t)      
                     }
@@ -1222,9 +4640,33 @@ impl TodoList {
                 None => t.none().normal(),
             };
             println!("{:<10}: {}", t.due_date(), due_date_str);
+            if let Some(repeat) = &task.repeat {
+                let due = task.due_date.unwrap_or_else(Local::now).date_naive();
+                let next = repeat.next_date(due, Local::now().date_naive());
+                println!("{:<10}: {}", t.repeat(), repeat);
+                println!("{:<10}: {}", t.next_occurrence(), next.format("%Y-%m-%d"));
+            }
+            for dependency in self.tasks.iter().filter(|dep| !dep.deleted && task.depends_on.contains(&dep.uid)) {
+                let status = if dependency.completed { "✓".green() } else { "✗".red() };
+                println!("{:<10}: {} {} {}", t.depends_on(), status, dependency.display_id().blue(), dependency.description);
+            }
+            if let Some(parent) = self.tasks.iter().find(|p| task.parent_id > 0 && Some(p.uid) == task.parent) {
+                println!("{:<10}: {} {}", t.parent_task(), parent.display_id().blue(), parent.description);
+            }
             println!("{}", "-".repeat(50));
             println!("{:<10}: ", t.description());
             println!("{}", task.description);
+            if !task.notes.is_empty() {
+                println!("{}", "-".repeat(50));
+                println!("{}:", t.notes());
+                println!("{}", task.notes);
+            }
+            let subtasks = self.subtasks(task);
+            if !subtasks.is_empty() {
+                println!("{}", "-".repeat(50));
+                println!("{}{}", t.subtasks(), subtask_progress(&subtasks));
+                self.print_subtasks(&subtasks, &t);
+            }
             println!("{}", "=".repeat(50));
             
             Ok(())
@@ -1233,35 +4675,676 @@ impl TodoList {
         }        
     }
 
-    fn save(&self) -> Result<(), io::Error> {
-        let contents = serde_json::to_string_pretty(&self.tasks)?;
-        let mut file = File::create(&self.file_path)?;
-        file.write_all(contents.as_bytes())?;   
-        Ok(())        
+    // 回收站中的任务，按删除时间从新到旧排列，序号即回收站中的ID
+    fn trashed_tasks(&self) -> Vec<&Task> {
+        // 随父任务一起删除的子任务不单独显示
+        let deleted_parents: Vec<Uuid> = self.tasks.iter()
+            .filter(|t| t.deleted && t.parent.is_none())
+            .map(|t| t.uid)
+            .collect();
+        let mut trashed: Vec<&Task> = self.tasks.iter()
+            .filter(|t| t.deleted && !t.parent.is_some_and(|uid| deleted_parents.contains(&uid)))
+            .collect();
+        trashed.sort_by_key(|t| std::cmp::Reverse(t.deleted_at));
+        trashed
+    }
+
+    // 显示回收站
+    fn list_trash(&self) {
+        let t = get_translations();
+        let trashed = self.trashed_tasks();
+
+        println!("{}", t.trash());
+        if trashed.is_empty() {
+            println!("  {}", t.none().italic());
+            return;
+        }
+
+        println!("{:<5} {:<40} {:<20}", t.id(), t.description(), t.deleted_at());
+        println!("{}", "-".repeat(70));
+
+        for (index, task) in trashed.iter().enumerate() {
+            let truncated_desc = truncate_str(&task.description, 36);
+            let formatted_desc = fixed_width_string(&truncated_desc, 40);
+            let deleted_at = match task.deleted_at {
+                Some(date) => date.format("%Y-%m-%d %H:%M").to_string().normal(),
+                None => t.none().normal(),
+            };
+
+            println!(
+                "{:<5} {} {}",
+                (index + 1).to_string().red(),
+                formatted_desc,
+                deleted_at
+            );
+        }
+    }
+
+    // 显示自定义视图：已完成和未完成的任务在同一张表中，按视图的排序方式排列
+    fn show_view(&self, view: &SavedView, format: OutputFormat) -> Result<(), String> {
+        let t = get_translations();
+        let filter = TaskFilter::from_args(&view.filter)?;
+        let sort = parse_sort(&view.sort)?;
+
+        let mut tasks: Vec<&Task> = self.tasks.iter()
+            .filter(|task| !task.deleted && filter.matches(task))
+            .collect();
+        sort_tasks(&mut tasks, &sort);
+
+        if format != OutputFormat::Text {
+            print_tasks(&tasks, format);
+            return Ok(());
+        }
+        if tasks.is_empty() {
+            println!("{}", t.no_tasks());
+            return Ok(());
+        }
+
+        let header: Vec<String> = view.columns.iter().map(|c| c.header(&t)).collect();
+        println!("{}", header.join(" ").trim_end());
+        println!("{}", "-".repeat(70));
+        for task in tasks {
+            let cells: Vec<String> = view.columns.iter().map(|c| c.cell(task, &t)).collect();
+            println!("{}", cells.join(" ").trim_end());
+        }
+        Ok(())
+    }
+
+    // 按截止日期分组显示未完成任务的日程视图
+    fn show_agenda(&self, view: AgendaView, format: OutputFormat) {
+        let t = get_translations();
+        let today = Local::now().date_naive();
+
+        let mut tasks: Vec<&Task> = self.tasks.iter()
+            .filter(|task| !task.deleted && !task.completed)
+            .filter(|task| task.due_date.is_some_and(|due| view.includes(&due, today)))
+            .collect();
+        tasks.sort_by_key(|task| (task.due_date, task.priority, task.id));
+
+        if format != OutputFormat::Text {
+            print_tasks(&tasks, format);
+            return;
+        }
+
+        println!("{}", view.title(&t));
+        if tasks.is_empty() {
+            println!("  {}", t.none().italic());
+            return;
+        }
+
+        let mut current_day = None;
+        for task in tasks {
+            let Some(due) = task.due_date else { continue };
+            let day = due.date_naive();
+
+            // 每天一个标题，如 "2026-10-20 星期二 (明天)"
+            if current_day != Some(day) {
+                current_day = Some(day);
+                let mut header = format!("{} {}", day.format("%Y-%m-%d"), t.weekday_name(day.weekday()));
+                match (day - today).num_days() {
+                    0 => header.push_str(&format!(" ({})", t.today())),
+                    1 => header.push_str(&format!(" ({})", t.tomorrow())),
+                    _ => {},
+                }
+                let header = if day < today { header.red().bold() } else { header.bold() };
+                println!("\n{}", header);
+            }
+
+            let time = if has_due_time(&due) { due.format("%H:%M").to_string() } else { String::new() };
+            let time = match get_due_status(Some(&due)) {
+                DueStatus::Overdue => time.red(),
+                _ => time.normal(),
+            };
+            println!(
+                "{}{:<5} {:<6} {} {}",
+                task.priority.marker(),
+                task.display_id().blue(),
+                time,
+                task.description,
+                format_tags(&task.tags)
+            );
+        }
+    }
+
+    // 显示统计信息；weeks不为空时按周统计完成数量，否则按天统计最近days天
+    fn show_stats(&self, days: u32, weeks: Option<u32>) {
+        let t = get_translations();
+        let today = Local::now().date_naive();
+        let tasks: Vec<&Task> = self.tasks.iter().filter(|task| !task.deleted).collect();
+
+        let is_overdue = |task: &Task| !task.completed && get_due_status(task.due_date.as_ref()) == DueStatus::Overdue;
+        let open = tasks.iter().filter(|task| !task.completed).count();
+        let completed = tasks.len() - open;
+        let overdue = tasks.iter().filter(|task| is_overdue(task)).count();
+
+        // 完成情况的统计同时包括已归档的任务，数量和标签只统计当前列表
+        let archive = match self.archived() {
+            Ok(archive) => archive.tasks,
+            Err(e) => {
+                eprintln!("{}", t.error(&e.to_string()));
+                Vec::new()
+            }
+        };
+        let history: Vec<&Task> = tasks.iter().copied().chain(archive.iter()).collect();
+
+        println!("{}", t.stats_title());
+        println!("{}", "=".repeat(50));
+        println!("{} {}", fixed_width_string(&t.stats_open(), 14), open.to_string().blue());
+        println!("{} {}", fixed_width_string(&t.stats_completed(), 14), completed.to_string().green());
+        println!("{} {}", fixed_width_string(&t.stats_overdue(), 14), overdue.to_string().red());
+
+        // 每天或每周完成的任务数，周从星期一开始
+        let buckets: Vec<(NaiveDate, NaiveDate)> = match weeks {
+            Some(weeks) => {
+                let monday = today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+                (0..weeks).rev()
+                    .filter_map(|i| monday.checked_sub_days(Days::new(u64::from(i) * 7)))
+                    .map(|start| (start, start + chrono::Duration::days(6)))
+                    .collect()
+            },
+            None => (0..days).rev()
+                .filter_map(|i| today.checked_sub_days(Days::new(i.into())))
+                .map(|day| (day, day))
+                .collect(),
+        };
+        let counts: Vec<usize> = buckets.iter()
+            .map(|(start, end)| {
+                history.iter()
+                    .filter_map(|task| task.completed_at)
+                    .filter(|done| (*start..=*end).contains(&done.date_naive()))
+                    .count()
+            })
+            .collect();
+
+        println!("\n{}", match weeks {
+            Some(weeks) => t.completed_per_week(weeks),
+            None => t.completed_per_day(days),
+        }.bold());
+        let max = counts.iter().copied().max().unwrap_or(0).max(1);
+        for ((start, _), count) in buckets.iter().zip(&counts) {
+            let label = match weeks {
+                Some(_) => start.format("%m-%d").to_string(),
+                None => format!("{} {}", start.format("%m-%d"), t.weekday_name(start.weekday())),
+            };
+            // 柱状图最长40个字符
+            let bar = "█".repeat((count * 40).div_ceil(max));
+            let gap = if bar.is_empty() { "" } else { " " };
+            println!("{} {}{}{}", fixed_width_string(&label, 16), bar.green(), gap, count);
+        }
+
+        // 平均用时：从创建到完成
+        let lead_times: Vec<chrono::Duration> = history.iter()
+            .filter_map(|task| task.completed_at.map(|done| done - task.created_at))
+            .collect();
+        println!();
+        if lead_times.is_empty() {
+            println!("{} {}", fixed_width_string(&t.average_lead_time(), 14), t.none());
+        } else {
+            let total: i64 = lead_times.iter().map(|d| d.num_minutes()).sum();
+            let average = chrono::Duration::minutes(total / lead_times.len() as i64);
+            println!("{} {}", fixed_width_string(&t.average_lead_time(), 14), t.duration(average));
+        }
+
+        // 按时完成率：有截止日期的已完成任务中，在截止日期（只有日期时为当天结束）之前完成的比例
+        let with_due: Vec<bool> = history.iter()
+            .filter_map(|task| {
+                let (due, done) = (task.due_date?, task.completed_at?);
+                Some(if has_due_time(&due) { done <= due } else { done.date_naive() <= due.date_naive() })
+            })
+            .collect();
+        if with_due.is_empty() {
+            println!("{} {}", fixed_width_string(&t.on_time_rate(), 14), t.none());
+        } else {
+            let on_time = with_due.iter().filter(|on_time| **on_time).count();
+            println!(
+                "{} {}% ({}/{})",
+                fixed_width_string(&t.on_time_rate(), 14),
+                on_time * 100 / with_due.len(),
+                on_time,
+                with_due.len()
+            );
+        }
+
+        // 各标签的未完成、已完成和过期数量
+        let mut by_tag: BTreeMap<&str, (usize, usize, usize)> = BTreeMap::new();
+        for task in &tasks {
+            for tag in &task.tags {
+                let entry = by_tag.entry(tag.as_str()).or_default();
+                if task.completed {
+                    entry.1 += 1;
+                } else {
+                    entry.0 += 1;
+                }
+                if is_overdue(task) {
+                    entry.2 += 1;
+                }
+            }
+        }
+        println!("\n{}", t.by_tag().bold());
+        if by_tag.is_empty() {
+            println!("  {}", t.none().italic());
+            return;
+        }
+        println!(
+            "{} {} {} {}",
+            fixed_width_string(&t.tags(), 20),
+            fixed_width_string(&t.stats_open(), 10),
+            fixed_width_string(&t.stats_completed(), 10),
+            t.stats_overdue()
+        );
+        println!("{}", "-".repeat(50));
+        for (tag, (open, completed, overdue)) in by_tag {
+            println!(
+                "{} {} {} {}",
+                fixed_width_string(&format!("+{}", tag), 20).cyan(),
+                fixed_width_string(&open.to_string(), 10),
+                fixed_width_string(&completed.to_string(), 10),
+                if overdue > 0 { overdue.to_string().red() } else { overdue.to_string().normal() }
+            );
+        }
+    }
+
+    // 搜索任务描述和备注，显示的ID可以直接用于其他命令（回收站中的任务显示回收站序号）
+    fn search(&self, pattern: &str, matcher: &Matcher, include_trash: bool, format: OutputFormat) {
+        let t = get_translations();
+        let trashed = self.trashed_tasks();
+
+        // (排序分组, 模糊匹配得分, 显示ID, 任务, 描述中的匹配范围, 备注中匹配的行及范围)
+        let mut results = Vec::new();
+        for task in &self.tasks {
+            if task.deleted && !include_trash {
+                continue;
+            }
+            // 描述不匹配时逐行搜索备注，取得分最好的一行
+            let (score, ranges, note_match) = if let Some((score, ranges)) = matcher.find(&task.description) {
+                (score, ranges, None)
+            } else if let Some((score, line, ranges)) = task.notes.lines()
+                .filter_map(|line| matcher.find(line).map(|(score, ranges)| (score, line, ranges)))
+                .min_by_key(|(score, _, _)| *score) {
+                (score, Vec::new(), Some((line, ranges)))
+            } else {
+                continue;
+            };
+            let (group, id) = if task.deleted {
+                let index = trashed.iter().position(|t| t.uid == task.uid).unwrap_or(0);
+                (2, (index + 1).to_string().red())
+            } else if task.completed {
+                (1, task.display_id().green())
+            } else {
+                (0, task.display_id().blue())
+            };
+            results.push((group, score, id, task, ranges, note_match));
+        }
+        results.sort_by_key(|(group, score, _, task, _, _)| (*score, *group, task.id));
+
+        if format != OutputFormat::Text {
+            let tasks: Vec<&Task> = results.iter().map(|(_, _, _, task, _, _)| *task).collect();
+            print_tasks(&tasks, format);
+            return;
+        }
+
+        if results.is_empty() {
+            println!("{}", t.no_search_results(pattern));
+            return;
+        }
+
+        println!("{}", t.search_results(pattern, results.len()));
+        println!("{:<5} {}", t.id(), t.description());
+        println!("{}", "-".repeat(70));
+        for (group, _, id, task, ranges, note_match) in &results {
+            let label = if *group == 2 { format!(" [{}]", t.in_trash()).dimmed().to_string() } else { String::new() };
+            println!("{:<5} {}{}", id, highlight(&task.description, ranges), label);
+            // 匹配的备注行显示在任务下方
+            if let Some((line, ranges)) = note_match {
+                println!("{:<5}   {} {}", "", format!("{}:", t.notes()).dimmed(), highlight(line, ranges));
+            }
+        }
+    }
+
+    // 从回收站恢复任务，ID为回收站中的序号或任务的UID
+    fn restore_tasks(&mut self, id_strs: &[String]) -> Result<Vec<String>, &'static str> {
+        let t = get_translations();
+
+        // 先将所有ID解析为UID，避免恢复过程中序号发生变化
+        let trashed = self.trashed_tasks();
+        let mut uids = Vec::new();
+        for id_str in id_strs {
+            let uid = match self.resolve_id(id_str)? {
+                TaskKey::Display { id, completed: false } if id >= 1 => {
+                    trashed.get(id - 1).map(|task| task.uid)
+                },
+                key @ TaskKey::Uid(_) => {
+                    trashed.iter().find(|task| key.matches(task, false)).map(|task| task.uid)
+                },
+                _ => None,
+            };
+            uids.push(uid.ok_or(t.task_not_exist())?);
+        }
+
+        // 同时删除的子任务与父任务一起恢复
+        let subtasks: Vec<Uuid> = self.tasks.iter()
+            .filter(|sub| sub.deleted && self.tasks.iter().any(|parent| {
+                uids.contains(&parent.uid) && sub.parent == Some(parent.uid) && sub.deleted_at == parent.deleted_at
+            }))
+            .map(|sub| sub.uid)
+            .collect();
+        uids.extend(subtasks);
+
+        // 恢复的任务排在同类任务的末尾，再由reassign_ids重新编号
+        let restored = self.tasks.iter_mut().filter(|task| task.deleted && uids.contains(&task.uid));
+        for (next_id, task) in (self.next_id..).zip(restored) {
+            task.deleted = false;
+            task.deleted_at = None;
+            task.id = next_id;
+        }
+
+        self.reassign_ids();
+        self.save(Operation::Restore).map_err(save_error)?;
+        Ok(id_strs.to_vec())
+    }
+
+    // 彻底删除回收站中的任务，可只删除在指定时长之前移入回收站的任务
+    // 旧版本删除的任务没有删除时间，只在未指定时长时删除
+    fn purge_trash(&mut self, older_than: Option<chrono::Duration>) -> Result<usize, &'static str> {
+        let now = Local::now();
+        let before = self.tasks.len();
+
+        self.tasks.retain(|task| {
+            if !task.deleted {
+                return true;
+            }
+            match (older_than, task.deleted_at) {
+                (Some(age), Some(deleted_at)) => now - deleted_at < age,
+                (Some(_), None) => true, // 旧版本删除时没有记录时间，无法判断是否过期
+                (None, _) => false,
+            }
+        });
+
+        let purged = before - self.tasks.len();
+        if purged > 0 {
+            self.save(Operation::Purge).map_err(save_error)?;
+        }
+        Ok(purged)
+    }
+
+    // 将完成超过指定时长的任务移入归档文件，子任务随父任务一起移动；返回归档的任务数量
+    // 未指定时长时归档所有已完成的任务；旧版本完成的任务没有完成时间，只在未指定时长时归档
+    fn archive_tasks(&mut self, older_than: Option<chrono::Duration>) -> Result<usize, &'static str> {
+        let now = Local::now();
+        let _lock = lock_tasks_file(&self.file_path).map_err(save_error)?;
+        self.reload_if_changed().map_err(save_error)?;
+
+        // 还有未完成子任务的任务暂不归档
+        let archived_uids: Vec<Uuid> = self.tasks.iter()
+            .filter(|task| !task.deleted && task.completed && task.parent_id == 0)
+            .filter(|task| match (older_than, task.completed_at) {
+                (Some(age), Some(completed_at)) => now - completed_at >= age,
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .filter(|task| self.subtasks(task).iter().all(|sub| sub.completed))
+            .map(|task| task.uid)
+            .collect();
+        if archived_uids.is_empty() {
+            return Ok(0);
+        }
+
+        let (archived, remaining): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|task| {
+                archived_uids.contains(&task.uid) || task.parent.is_some_and(|uid| archived_uids.contains(&uid))
+            });
+        self.tasks = remaining;
+        let count = archived.len();
+
+        // 先写入归档文件：即使随后保存列表失败，归档中与列表重复的任务在查看时也会被忽略
+        self.update_archive(&[], archived)?;
+
+        self.reassign_ids();
+        self.save_locked(Some(Operation::Archive)).map_err(save_error)?;
+        Ok(count)
+    }
+
+    // 从归档文件中移除指定UID的任务，并加入新归档的任务（替换归档中同一任务原有的内容）
+    fn update_archive(&self, remove: &[Uuid], add: Vec<Task>) -> Result<(), &'static str> {
+        let t = get_translations();
+        let archive_path = get_archive_path(&self.file_path);
+        let (mut archive, _) = read_tasks_file(&archive_path).map_err(|_| t.save_failed())?;
+        archive.retain(|task| !remove.contains(&task.uid) && !add.iter().any(|a| a.uid == task.uid));
+        archive.extend(add);
+        let contents = serde_json::to_string_pretty(&archive).map_err(|_| t.save_failed())?;
+        write_atomic(&archive_path, contents.as_bytes()).map_err(save_error)
+    }
+
+    // 按列表中的顺序（未完成的在前，子任务紧跟在父任务之后）返回所有未删除的任务，用于导出
+    fn export_order(&self) -> Vec<&Task> {
+        let mut top_level: Vec<&Task> = self.tasks.iter()
+            .filter(|task| !task.deleted && task.parent_id == 0)
+            .collect();
+        top_level.sort_by_key(|task| (task.completed, task.id));
+        top_level.into_iter()
+            .flat_map(|task| std::iter::once(task).chain(self.subtasks(task)))
+            .collect()
+    }
+
+    // 读取归档文件中的任务，只在 --archived 和 stats 时调用；归档后保存列表失败时留下的重复任务不再显示
+    fn archived(&self) -> Result<TodoList, io::Error> {
+        let file_path = get_archive_path(&self.file_path);
+        let (mut tasks, contents) = read_tasks_file(&file_path)?;
+        tasks.retain(|task| !self.tasks.iter().any(|live| live.uid == task.uid));
+
+        let mut archive = TodoList {
+            tasks,
+            file_path,
+            next_id: 1,
+            last_synced: contents,
+            last_changes: Vec::new(),
+        };
+        archive.reassign_ids();
+        Ok(archive)
+    }
+
+    fn save(&mut self, operation: Operation) -> Result<(), io::Error> {
+        let _lock = lock_tasks_file(&self.file_path)?;
+        self.save_locked(Some(operation))
+    }
+
+    // 在已持有锁的情况下保存；若文件自加载后被其他进程修改，则拒绝覆盖
+    // 指定operation时，将本次改动记录到操作日志中以便撤销
+    fn save_locked(&mut self, operation: Option<Operation>) -> Result<(), io::Error> {
+        let on_disk = if self.file_path.exists() {
+            fs::read_to_string(&self.file_path)?
+        } else {
+            String::new()
+        };
+        if on_disk != self.last_synced {
+            return Err(io::Error::other(ListChanged));
+        }
+
+        let old_tasks: Vec<Task> = serde_json::from_str(&self.last_synced).unwrap_or_default();
+        let (before, after) = diff_tasks(&old_tasks, &self.tasks);
+        // 修改后的任务，加上被彻底删除的任务；没有变化时清空，以免输出之前（如自动归档）的改动
+        self.last_changes = after.clone();
+        self.last_changes.extend(before.iter().filter(|b| !after.iter().any(|a| a.uid == b.uid)).cloned());
+
+        let entry = operation.and_then(|operation| {
+            if before.is_empty() && after.is_empty() {
+                None
+            } else {
+                Some(JournalEntry {
+                    operation,
+                    at: Local::now(),
+                    before,
+                    after,
+                    order_before: display_order(&old_tasks),
+                    order_after: display_order(&self.tasks),
+                })
+            }
+        });
+
+        let contents = serde_json::to_string_pretty(&self.tasks)?;
+        write_atomic(&self.file_path, contents.as_bytes())?;
+        self.last_synced = contents;
+
+        if let Some(entry) = entry {
+            let journal_path = get_journal_path(&self.file_path);
+            let mut journal = Journal::load(&journal_path);
+            journal.record(entry);
+            journal.save(&journal_path)?;
+        }
+        Ok(())
+    }
+
+    // 最近一次保存时发生变化的任务（使用当前的显示ID）
+    fn changed_tasks(&self) -> Vec<Task> {
+        self.last_changes.iter()
+            .map(|changed| {
+                self.tasks.iter()
+                    .find(|task| task.uid == changed.uid)
+                    .unwrap_or(changed)
+                    .clone()
+            })
+            .collect()
+    }
+
+    // 以指定格式输出所有未删除且符合条件的任务
+    fn print_list(&self, format: OutputFormat, filter: &TaskFilter, sort: &[SortKey]) {
+        if format == OutputFormat::Text {
+            self.list_tasks(filter, sort);
+            return;
+        }
+        let mut visible: Vec<&Task> = self.tasks.iter()
+            .filter(|task| !task.deleted && filter.matches(task))
+            .collect();
+        visible.sort_by_key(|task| task.id);
+        sort_tasks(&mut visible, sort);
+        print_tasks(&visible, format);
+    }
+
+    // 撤销最近一次尚未撤销的操作，返回被撤销的操作类型
+    fn undo_last(&mut self) -> Result<Operation, &'static str> {
+        let t = get_translations();
+        let _lock = lock_tasks_file(&self.file_path).map_err(|_| t.save_failed())?;
+        let journal_path = get_journal_path(&self.file_path);
+        let mut journal = Journal::load(&journal_path);
+
+        if journal.position == 0 {
+            return Err(t.nothing_to_undo());
+        }
+        let entry = journal.entries[journal.position - 1].clone();
+        apply_changes(&mut self.tasks, &entry.after, &entry.before, &entry.order_before);
+        self.reassign_ids();
+        self.save_locked(None).map_err(save_error)?;
+
+        journal.position -= 1;
+        journal.save(&journal_path).map_err(|_| t.save_failed())?;
+
+        // 撤销归档时任务回到列表，同时从归档文件中移除
+        if entry.operation == Operation::Archive {
+            let uids: Vec<Uuid> = entry.before.iter().map(|task| task.uid).collect();
+            self.update_archive(&uids, Vec::new())?;
+        }
+        Ok(entry.operation)
+    }
+
+    // 重做最近一次被撤销的操作，返回被重做的操作类型
+    fn redo(&mut self) -> Result<Operation, &'static str> {
+        let t = get_translations();
+        let _lock = lock_tasks_file(&self.file_path).map_err(|_| t.save_failed())?;
+        let journal_path = get_journal_path(&self.file_path);
+        let mut journal = Journal::load(&journal_path);
+
+        if journal.position >= journal.entries.len() {
+            return Err(t.nothing_to_redo());
+        }
+        let entry = journal.entries[journal.position].clone();
+        // 重做归档时先把任务写回归档文件，再从列表中移除
+        if entry.operation == Operation::Archive {
+            self.update_archive(&[], entry.before.clone())?;
+        }
+        apply_changes(&mut self.tasks, &entry.before, &entry.after, &entry.order_after);
+        self.reassign_ids();
+        self.save_locked(None).map_err(save_error)?;
+
+        journal.position += 1;
+        journal.save(&journal_path).map_err(|_| t.save_failed())?;
+        Ok(entry.operation)
+    }
+
+    // 显示操作日志，最新的操作在前，已撤销的操作以灰色显示
+    fn show_log(&self) {
+        let t = get_translations();
+        let journal = Journal::load(&get_journal_path(&self.file_path));
+
+        println!("{}", t.operation_log());
+        if journal.entries.is_empty() {
+            println!("  {}", t.none().italic());
+            return;
+        }
+
+        println!("{:<5} {:<18} {:<12} {}", "#", t.time(), t.operation(), t.description());
+        println!("{}", "-".repeat(70));
+
+        for (index, entry) in journal.entries.iter().enumerate().rev() {
+            // 优先显示修改后的任务内容，删除类操作则显示修改前的内容
+            let affected = if entry.after.is_empty() { &entry.before } else { &entry.after };
+            let descriptions = affected.iter()
+                .map(|task| task.description.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let line = format!(
+                "{:<5} {:<18} {} {}",
+                index + 1,
+                entry.at.format("%Y-%m-%d %H:%M").to_string(),
+                fixed_width_string(&t.operation_name(entry.operation), 12),
+                truncate_str(&descriptions, 32)
+            );
+
+            if index < journal.position {
+                println!("{}", line);
+            } else {
+                println!("{} {}", line.dimmed(), t.undone_marker().dimmed());
+            }
+        }
+    }
+
+    // 解析任务ID；UID前缀匹配到多个任务时报错，避免操作任意一个匹配的任务
+    fn resolve_id(&self, id_str: &str) -> Result<TaskKey, &'static str> {
+        let key = parse_task_id(id_str);
+        if let TaskKey::Uid(_) = key {
+            if self.tasks.iter().filter(|task| key.matches(task, false)).count() > 1 {
+                return Err(get_translations().ambiguous_id());
+            }
+        }
+        Ok(key)
     }
 
-    #[allow(dead_code)]
-    fn get_task(&self, id_str: &str) -> Option<&Task> {
-        let (id, is_completed) = parse_task_id(id_str);
+    fn get_task(&self, id_str: &str) -> Result<&Task, &'static str> {
+        let key = self.resolve_id(id_str)?;
     
         self.tasks.iter()   
-            .find(|t| !t.deleted && t.completed == is_completed && t.id == id)
+            .find(|t| !t.deleted && key.matches(t, true))
+            .ok_or(get_translations().task_not_exist())
     }
     fn reassign_ids(&mut self) {
+        // 父任务不存在或已删除的子任务按顶层任务编号
+        let parents: Vec<Uuid> = self.tasks.iter()
+            .filter(|task| !task.deleted && task.parent.is_none())
+            .map(|task| task.uid)
+            .collect();
+        let is_top_level = |task: &Task| !matches!(task.parent, Some(uid) if parents.contains(&uid));
+        for task in self.tasks.iter_mut() {
+            task.parent_id = 0;
+        }
+
         let mut next_id = 1;
         // 先处理未完成的任务
         let mut incomplete_tasks: Vec<&mut Task> = self.tasks.iter_mut()
-            .filter(|task| !task.deleted && !task.completed)        
+            .filter(|task| !task.deleted && !task.completed && is_top_level(task))        
             .collect();
     
-        // 排序规则（星标优先，然后按原ID）
-        incomplete_tasks.sort_by(|a, b| {
-            match (a.starred, b.starred) {
-                (true, false) => Ordering::Less,
-                (false, true) => Ordering::Greater,   
-                _ => a.id.cmp(&b.id)     
-            }        
-        });
+        // 排序规则（优先级高的在前，然后按原ID）
+        incomplete_tasks.sort_by_key(|t| (t.priority, t.id));
     
         for task in incomplete_tasks {
             task.id = next_id;   
@@ -1270,16 +5353,33 @@ impl TodoList {
     
         // 再处理已完成的任务
         let mut completed_tasks: Vec<&mut Task> = self.tasks.iter_mut()
-            .filter(|task| !task.deleted && task.completed)        
+            .filter(|task| !task.deleted && task.completed && is_top_level(task))        
             .collect();
     
-        // 严格按照原ID排序
-        completed_tasks.sort_by_key(|t| t.id);
+        // 按完成时间排序，旧版本完成的任务没有完成时间，排在最前并保持原ID顺序
+        completed_tasks.sort_by_key(|t| (t.completed_at, t.id));
                 
         for task in completed_tasks {
             task.id = next_id;   
             next_id += 1;        
         }
+
+        // 子任务在父任务内从1开始编号，保持原有顺序
+        let parent_ids: Vec<(Uuid, usize)> = self.tasks.iter()
+            .filter(|task| parents.contains(&task.uid))
+            .map(|task| (task.uid, task.id))
+            .collect();
+        for (uid, parent_id) in parent_ids {
+            let mut subtasks: Vec<&mut Task> = self.tasks.iter_mut()
+                .filter(|task| !task.deleted && task.parent == Some(uid))
+                .collect();
+            subtasks.sort_by_key(|t| t.id);
+
+            for (index, task) in subtasks.into_iter().enumerate() {
+                task.id = index + 1;
+                task.parent_id = parent_id;
+            }
+        }
     
         // 更新下一个可用ID   
         self.next_id = next_id;        
@@ -1290,12 +5390,15 @@ impl TodoList {
         let mut completed_ids = Vec::new(); 
     
         for id_str in id_strs {    
-            let (id, _) = parse_task_id(id_str);
+            let key = self.resolve_id(id_str)?;
     
             if let Some(task) = self.tasks.iter_mut()
-                .find(|t| !t.deleted && !t.completed && t.id == id) {
+                .find(|t| !t.deleted && !t.completed && key.matches(t, false)) {
                 task.completed = true;   
+                task.completed_at = Some(Local::now());
                 completed_ids.push(id_str.clone());       
+                let uid = task.uid;
+                self.spawn_next_occurrence(uid);
             }        
         }
     
@@ -1304,7 +5407,7 @@ impl TodoList {
         }
             
         self.reassign_ids(); // 重新分配ID    
-        self.save().map_err(|_| t.save_failed())?;
+        self.save(Operation::Complete).map_err(save_error)?;
     
         Ok(completed_ids)       
     }    
@@ -1314,16 +5417,17 @@ impl TodoList {
         let mut uncompleted_ids = Vec::new(); 
     
         for id_str in id_strs {    
-            let (id, is_completed) = parse_task_id(id_str);
+            let key = self.resolve_id(id_str)?;
             
             // 只处理已完成的任务
-            if !is_completed && id > 0 {
+            if let TaskKey::Display { completed: false, .. } = key {
                 continue;
             }
     
             if let Some(task) = self.tasks.iter_mut()
-                .find(|t| !t.deleted && t.completed && t.id == id) {
+                .find(|t| !t.deleted && t.completed && key.matches(t, false)) {
                 task.completed = false;   
+                task.completed_at = None;
                 uncompleted_ids.push(id_str.clone());       
             }        
         }
@@ -1333,7 +5437,7 @@ impl TodoList {
         }
             
         self.reassign_ids(); // 重新分配ID    
-        self.save().map_err(|_| t.save_failed())?;
+        self.save(Operation::Reopen).map_err(save_error)?;
     
         Ok(uncompleted_ids)       
     }
@@ -1344,10 +5448,10 @@ impl TodoList {
         let mut updated_ids = Vec::new();
 
         for id_str in id_strs {
-            let (id, is_completed) = parse_task_id(id_str);
+            let key = self.resolve_id(id_str)?;
             
             if let Some(task) = self.tasks.iter_mut()
-                .find(|t| !t.deleted && t.completed == is_completed && t.id == id) {
+                .find(|t| !t.deleted && key.matches(t, true)) {
                 task.due_date = Some(due_date);
                 updated_ids.push(id_str.clone());
             }
@@ -1357,45 +5461,375 @@ impl TodoList {
             return Err(t.task_not_exist());
         }
 
-        self.save().map_err(|_| t.save_failed())?;
+        self.save(Operation::Edit).map_err(save_error)?;
         Ok(updated_ids)
     }
 }
+// 在编辑器中编辑任务时使用的文本格式：每个任务以 [显示ID @UID] 开头，随后是 "字段: 值"，
+// 备注写在 "notes:" 之后的缩进行中。只有与原值不同的字段才会被修改
+const DOCUMENT_FIELDS: [&str; 7] = ["description", "due", "priority", "repeat", "tags", "completed", "notes"];
+
+// 编辑结果中发生变化的字段，None表示不修改
+#[derive(Debug, Default)]
+struct TaskEdit {
+    uid: Uuid,
+    description: Option<String>,
+    due_date: Option<Option<DateTime<Local>>>,
+    priority: Option<Priority>,
+    repeat: Option<Option<Repeat>>,
+    tags: Option<Vec<String>>,
+    completed: Option<bool>,
+    notes: Option<String>,
+}
+
+impl TaskEdit {
+    fn is_empty(&self) -> bool {
+        self.description.is_none() && self.due_date.is_none() && self.priority.is_none() && self.repeat.is_none()
+            && self.tags.is_none() && self.completed.is_none() && self.notes.is_none()
+    }
+}
+
+// 任务各字段在文档中的文本形式
+fn document_fields(task: &Task) -> Vec<(&'static str, String)> {
+    let priority = match task.priority {
+        Priority::High => "high",
+        Priority::Medium => "medium",
+        Priority::Low => "low",
+        Priority::None => "none",
+    };
+    vec![
+        ("description", task.description.clone()),
+        ("due", task.due_date.as_ref().map(format_due).unwrap_or_default()),
+        ("priority", priority.to_string()),
+        ("repeat", task.repeat.as_ref().map(|rule| rule.to_string()).unwrap_or_default()),
+        ("tags", task.tags.join(" ")),
+        ("completed", if task.completed { "yes" } else { "no" }.to_string()),
+        ("notes", task.notes.clone()),
+    ]
+}
+
+// 将任务转换为可编辑的文档
+fn tasks_to_document(tasks: &[&Task]) -> String {
+    let mut document = get_translations().edit_document_header();
+    for task in tasks {
+        document.push_str(&format!("\n[{} @{}]\n", task.display_id(), task.uid));
+        for (key, value) in document_fields(task) {
+            if key != "notes" {
+                document.push_str(format!("{}: {}", key, value).trim_end());
+                document.push('\n');
+                continue;
+            }
+            document.push_str("notes:\n");
+            for line in value.lines() {
+                document.push_str(&format!("  {}\n", line));
+            }
+        }
+    }
+    document
+}
+
+// 文档中一个任务的字段：字段名 -> (所在行号, 值)
+type DocumentFields<'a> = BTreeMap<&'a str, (usize, String)>;
+
+// 解析编辑后的文档，与原任务比较得到修改内容；出错时返回按行号排列的 (行号, 错误信息)
+fn parse_document(text: &str, originals: &[&Task]) -> Result<Vec<TaskEdit>, Vec<(usize, String)>> {
+    let t = get_translations();
+    let mut errors = Vec::new();
+    let mut sections: Vec<(&Task, DocumentFields)> = Vec::new();
+    let mut in_notes = false;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_no = index + 1;
+
+        // 备注内容为缩进的行，中间的空行也属于备注
+        if in_notes && (line.starts_with("  ") || line.trim().is_empty()) {
+            if let Some((_, fields)) = sections.last_mut() {
+                if let Some((_, notes)) = fields.get_mut("notes") {
+                    notes.push_str(line.strip_prefix("  ").unwrap_or(""));
+                    notes.push('\n');
+                }
+            }
+            continue;
+        }
+        in_notes = false;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            let uid = header.rsplit_once('@').map(|(_, uid)| uid.trim()).unwrap_or_default();
+            match originals.iter().find(|task| task.uid.to_string() == uid) {
+                Some(task) if sections.iter().any(|(seen, _)| seen.uid == task.uid) => {
+                    errors.push((line_no, t.duplicate_task_section(header)));
+                },
+                Some(task) => sections.push((task, BTreeMap::new())),
+                None => errors.push((line_no, t.unknown_task_section(header))),
+            }
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            errors.push((line_no, t.invalid_document_line(trimmed)));
+            continue;
+        };
+        let key = key.trim();
+        let Some(key) = DOCUMENT_FIELDS.iter().copied().find(|field| *field == key) else {
+            errors.push((line_no, t.unknown_field(key)));
+            continue;
+        };
+        let Some((_, fields)) = sections.last_mut() else {
+            errors.push((line_no, t.field_outside_task(key)));
+            continue;
+        };
+
+        // "notes:" 同一行的内容作为备注的第一行
+        in_notes = key == "notes";
+        let mut value = value.trim().to_string();
+        if in_notes && !value.is_empty() {
+            value.push('\n');
+        }
+        fields.insert(key, (line_no, value));
+    }
+
+    let mut edits = Vec::new();
+    for (task, fields) in sections {
+        let mut edit = TaskEdit { uid: task.uid, ..TaskEdit::default() };
+
+        for (key, original) in document_fields(task) {
+            let Some((line_no, value)) = fields.get(key) else {
+                continue;
+            };
+            let value = if key == "notes" { value.trim_end().to_string() } else { value.clone() };
+            if value == original.trim_end() {
+                continue;
+            }
+
+            let result: Result<(), String> = match key {
+                "description" if value.is_empty() => Err(t.empty_description().to_string()),
+                "description" => {
+                    edit.description = Some(value);
+                    Ok(())
+                },
+                "due" if value.is_empty() || value == "none" => {
+                    edit.due_date = Some(None);
+                    Ok(())
+                },
+                "due" => parse_date(&value)
+                    .map(|date| edit.due_date = Some(Some(date)))
+                    .map_err(|e| e.to_string()),
+                "priority" => parse_priority(&value).map(|priority| edit.priority = Some(priority)),
+                "repeat" if value.is_empty() => {
+                    edit.repeat = Some(None);
+                    Ok(())
+                },
+                "repeat" => parse_repeat(&value).map(|repeat| edit.repeat = Some(repeat)),
+                "tags" => value.split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|word| !word.is_empty())
+                    .map(|word| {
+                        let word = word.strip_prefix('+').unwrap_or(word);
+                        match parse_tag(&format!("+{}", word)) {
+                            Some((true, tag)) => Ok(tag),
+                            _ => Err(t.invalid_tag(word)),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(|tags| edit.tags = Some(tags)),
+                "completed" => match value.to_lowercase().as_str() {
+                    "yes" | "y" | "true" | "x" => {
+                        edit.completed = Some(true);
+                        Ok(())
+                    },
+                    "no" | "n" | "false" => {
+                        edit.completed = Some(false);
+                        Ok(())
+                    },
+                    _ => Err(t.invalid_completed(&value)),
+                },
+                _ => {
+                    edit.notes = Some(value);
+                    Ok(())
+                },
+            };
+            if let Err(e) = result {
+                errors.push((*line_no, format!("{}: {}", key, e)));
+            }
+        }
+
+        if !edit.is_empty() {
+            edits.push(edit);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(edits)
+    } else {
+        errors.sort_by_key(|(line_no, _)| *line_no);
+        Err(errors)
+    }
+}
+
+// 在编辑器中编辑一组任务，文档有错误时带着错误提示重新打开，直到修正、清空文档或未作修改
+fn edit_tasks_in_editor(todo_list: &mut TodoList, format: OutputFormat, uids: &[Uuid]) {
+    let t = get_translations();
+    let originals: Vec<Task> = uids.iter()
+        .filter_map(|uid| todo_list.tasks.iter().find(|task| task.uid == *uid))
+        .cloned()
+        .collect();
+    let originals: Vec<&Task> = originals.iter().collect();
+    if originals.is_empty() {
+        println!("{}", t.no_tasks());
+        return;
+    }
+
+    let mut document = tasks_to_document(&originals);
+    let mut previous: Option<String> = None;
+
+    let edits = loop {
+        let edited = match edit_in_editor(&document, "jodo-edit", "txt") {
+            Ok(edited) => edited,
+            Err(e) => {
+                eprintln!("{}", t.error(&e.to_string()));
+                return;
+            }
+        };
+
+        // 清空文档或出错后未作修改视为取消
+        let content = strip_document_errors(&edited);
+        if content.trim().is_empty() || previous.as_deref() == Some(content.as_str()) {
+            println!("{}", t.edit_cancelled());
+            return;
+        }
+
+        match parse_document(&content, &originals) {
+            Ok(edits) => break edits,
+            Err(errors) => {
+                let errors = document_error_messages(&errors);
+                for error in &errors {
+                    eprintln!("{}", t.error(error));
+                }
+                document = errors.iter()
+                    .map(|error| format!("{} {}\n", DOCUMENT_ERROR_PREFIX, error))
+                    .collect::<String>() + &content;
+                previous = Some(content);
+            },
+        }
+    };
+
+    if edits.is_empty() {
+        println!("{}", t.no_changes());
+        return;
+    }
+    // 与 done 相同，完成依赖的任务尚未完成的任务时给出警告
+    let completing: Vec<String> = edits.iter()
+        .filter(|edit| edit.completed == Some(true))
+        .filter_map(|edit| todo_list.tasks.iter().find(|task| task.uid == edit.uid))
+        .map(|task| task.display_id())
+        .collect();
+    let warnings = todo_list.open_dependency_warnings(&completing);
+    match todo_list.apply_edits(edits) {
+        Ok(count) => {
+            print_result(todo_list, format, &t.tasks_edited(count));
+            warnings.iter().for_each(|warning| eprintln!("{}", warning.yellow()));
+        },
+        Err(e) => eprintln!("{}", t.cmd_error(e)),
+    }
+}
+
+// 重新打开编辑器时，错误提示以此开头写在文档顶部
+const DOCUMENT_ERROR_PREFIX: &str = "# ERROR:";
+
+// 错误提示写在重新打开的文档顶部，行号对应重新打开后的位置
+fn document_error_messages(errors: &[(usize, String)]) -> Vec<String> {
+    let t = get_translations();
+    errors.iter()
+        .map(|(line_no, message)| t.document_error(line_no + errors.len(), message))
+        .collect()
+}
+
+// 去掉上一次写在文档顶部的错误提示
+fn strip_document_errors(edited: &str) -> String {
+    edited.lines()
+        .filter(|line| !line.starts_with(DOCUMENT_ERROR_PREFIX))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+// 解析截止日期，可以带时间，如 "2026-10-20 14:30"、"tomorrow 9am"、"14:30"（今天）
+// 不带时间时为当天零点，表示截止到当天结束
 fn parse_date(date_str: &str) -> Result<DateTime<Local>, &'static str> {
     let t = get_translations();
-    let now = Local::now();
+    let (date_part, time) = split_date_time(date_str.trim());
+    let date = parse_day(date_part)?;
+
+    local_datetime(date, time.unwrap_or(NaiveTime::MIN)).ok_or(t.invalid_datetime())
+}
+
+// 从末尾拆出时间部分（以空格或ISO格式的'T'分隔），只有时间时日期部分为空（即今天）
+fn split_date_time(date_str: &str) -> (&str, Option<NaiveTime>) {
+    if let Some(time) = parse_time(date_str) {
+        return ("", Some(time));
+    }
+    if let Some((date, time)) = date_str.rsplit_once([' ', 'T']) {
+        if let Some(time) = parse_time(time) {
+            return (date.trim(), Some(time));
+        }
+    }
+    (date_str, None)
+}
+
+// 解析时间：14:30、9:05、9am、12pm、9:30pm
+fn parse_time(time_str: &str) -> Option<NaiveTime> {
+    let time_str = time_str.trim().to_lowercase();
+    let (clock, meridiem) = if let Some(clock) = time_str.strip_suffix("am") {
+        (clock.trim(), Some(false))
+    } else if let Some(clock) = time_str.strip_suffix("pm") {
+        (clock.trim(), Some(true))
+    } else {
+        (time_str.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // 不带分钟时必须有am/pm，避免与"20"（当月20日）混淆
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// 解析日期部分（不含时间）
+fn parse_day(date_str: &str) -> Result<NaiveDate, &'static str> {
+    let t = get_translations();
+    let today = Local::now().date_naive();
     
     // 处理特殊关键字
     match date_str.to_lowercase().as_str() {
-        "" | "today" | "td" => {
-            // 今天，使用当前日期的00:00:00
-            let today = now.date_naive().and_hms_opt(0, 0, 0)
-                .ok_or(t.invalid_datetime())?;
-            return Ok(DateTime::from_naive_utc_and_offset(today, now.offset().clone()));
-        },
-        "tomorrow" | "tm" => {
-            // 明天，使用当前日期+1天的00:00:00
-            let tomorrow = (now + chrono::Duration::days(1)).date_naive()
-                .and_hms_opt(0, 0, 0).ok_or(t.invalid_datetime())?;
-            return Ok(DateTime::from_naive_utc_and_offset(tomorrow, now.offset().clone()));
-        },
+        "" | "today" | "td" => return Ok(today),
+        "tomorrow" | "tm" => return Ok(today + chrono::Duration::days(1)),
         _ => {}
     }
+
+    // 相对日期，如 "fri"、"next monday"、"+3d"、"end of month"，以及当前语言的关键字
+    if let Some(date) = parse_relative_day(date_str, today) {
+        return Ok(date);
+    }
     
     // 尝试解析不同格式的日期
     
     // 1. 只有一个数字，解释为当月的某一天
     if let Ok(day) = date_str.parse::<u32>() {
-        if day >= 1 && day <= 31 {
-            let year = now.year();
-            let month = now.month();
-            
-            if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
-                let datetime = date.and_hms_opt(0, 0, 0).ok_or(t.invalid_datetime())?;
-                return Ok(DateTime::from_naive_utc_and_offset(datetime, now.offset().clone()));
-            } else {
-                return Err(t.invalid_date_format());
-            }
+        if (1..=31).contains(&day) {
+            return NaiveDate::from_ymd_opt(today.year(), today.month(), day)
+                .ok_or(t.invalid_date_format());
         }
     }
     
@@ -1405,26 +5839,232 @@ fn parse_date(date_str: &str) -> Result<DateTime<Local>, &'static str> {
         let day_str = &day_str[1..]; // 去掉'-'
         
         if let (Ok(month), Ok(day)) = (month_str.parse::<u32>(), day_str.parse::<u32>()) {
-            if month >= 1 && month <= 12 && day >= 1 && day <= 31 {
-                let year = now.year();
-                
-                if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
-                    let datetime = date.and_hms_opt(0, 0, 0).ok_or(t.invalid_datetime())?;
-                    return Ok(DateTime::from_naive_utc_and_offset(datetime, now.offset().clone()));
+            if (1..=12).contains(&month) && (1..=31).contains(&day) {
+                if let Some(date) = NaiveDate::from_ymd_opt(today.year(), month, day) {
+                    return Ok(date);
                 }
             }
         }
     }
     
     // 3. 标准YYYY-MM-DD格式
-    let naive_date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-        .map_err(|_| t.invalid_date_format())?;
-    
-    // 修改为00:00:00而不是23:59:59，避免时区转换导致日期偏移
-    let naive_datetime = naive_date.and_hms_opt(0, 0, 0)
-        .ok_or(t.invalid_datetime())?;
-       
-    Ok(DateTime::from_naive_utc_and_offset(naive_datetime, Local::now().offset().clone()))
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+        .map_err(|_| t.invalid_date_format())
+}
+
+// 解析相对日期：英文关键字始终可用，中文和日文关键字只在对应语言下可用
+fn parse_relative_day(date_str: &str, today: NaiveDate) -> Option<NaiveDate> {
+    parse_relative_day_in(date_str, today, get_translations().lang)
+}
+
+fn parse_relative_day_in(date_str: &str, today: NaiveDate, lang: Language) -> Option<NaiveDate> {
+    let text = date_str.trim().to_lowercase();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match lang {
+        Language::Chinese => parse_chinese_day(&text, today),
+        Language::Japanese => parse_japanese_day(&text, today),
+        Language::English => None,
+    }.or_else(|| parse_english_day(&text, today))
+}
+
+fn parse_english_day(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text {
+        "next week" => return Some(next_week_day(today, Weekday::Mon)),
+        "next month" => return add_months(today.with_day(1)?, 1),
+        "end of month" | "eom" => return Some(clamp_day(today.year(), today.month(), 31)),
+        "yesterday" | "yd" => return sub_offset(today, 1, "d"),
+        "last week" | "last-week" => return sub_offset(today, 1, "w"),
+        "last month" | "last-month" => return sub_offset(today, 1, "m"),
+        _ => {}
+    }
+
+    // 过去的日期：-3d、-2w、-1m，或 3 days ago
+    if let Some(offset) = text.strip_prefix('-') {
+        let split_at = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (num, unit) = offset.split_at(split_at);
+        return sub_offset(today, num.parse().ok()?, unit);
+    }
+    if let Some(offset) = text.strip_suffix(" ago") {
+        let (num, unit) = offset.split_once(' ')?;
+        return sub_offset(today, num.parse().ok()?, unit);
+    }
+
+    // 偏移量：+3d、+2w、+1m，或 in 3 days、in 2 weeks、in 1 month
+    if let Some(offset) = text.strip_prefix('+') {
+        let split_at = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (num, unit) = offset.split_at(split_at);
+        return add_offset(today, num.parse().ok()?, unit);
+    }
+    if let Some(offset) = text.strip_prefix("in ") {
+        let (num, unit) = offset.split_once(' ')?;
+        return add_offset(today, num.parse().ok()?, unit);
+    }
+
+    // 星期：fri 为今天或之后最近的星期五，next fri 为下周的星期五
+    if let Some(day) = text.strip_prefix("next ") {
+        return day.parse::<Weekday>().ok().map(|wd| next_week_day(today, wd));
+    }
+    text.parse::<Weekday>().ok().map(|wd| weekday_on_or_after(today, wd))
+}
+
+fn parse_chinese_day(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text {
+        "今天" => return Some(today),
+        "明天" => return Some(today + chrono::Duration::days(1)),
+        "后天" => return Some(today + chrono::Duration::days(2)),
+        "下周" => return Some(next_week_day(today, Weekday::Mon)),
+        "下个月" | "下月" => return add_months(today.with_day(1)?, 1),
+        "月底" => return Some(clamp_day(today.year(), today.month(), 31)),
+        "昨天" => return sub_offset(today, 1, "d"),
+        "上周" => return sub_offset(today, 1, "w"),
+        "上个月" | "上月" => return sub_offset(today, 1, "m"),
+        _ => {}
+    }
+
+    // 偏移量：3天后、2周后、1个月后，或 3天前 等过去的日期
+    let (offset, past) = match (text.strip_suffix('后'), text.strip_suffix('前')) {
+        (Some(offset), _) => (Some(offset), false),
+        (_, Some(offset)) => (Some(offset), true),
+        _ => (None, false),
+    };
+    if let Some(offset) = offset {
+        let split_at = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (num, unit) = offset.split_at(split_at);
+        let unit = match unit {
+            "天" => "d",
+            "周" | "星期" => "w",
+            "个月" | "月" => "m",
+            _ => return None,
+        };
+        let num = num.parse().ok()?;
+        return if past { sub_offset(today, num, unit) } else { add_offset(today, num, unit) };
+    }
+
+    // 星期：周五、星期五、下周一
+    let weekday = |name: &str| {
+        let name = name.strip_prefix("星期").or_else(|| name.strip_prefix("周"))?;
+        match name {
+            "一" => Some(Weekday::Mon),
+            "二" => Some(Weekday::Tue),
+            "三" => Some(Weekday::Wed),
+            "四" => Some(Weekday::Thu),
+            "五" => Some(Weekday::Fri),
+            "六" => Some(Weekday::Sat),
+            "日" | "天" => Some(Weekday::Sun),
+            _ => None,
+        }
+    };
+    if let Some(day) = text.strip_prefix("下") {
+        return weekday(day).map(|wd| next_week_day(today, wd));
+    }
+    weekday(text).map(|wd| weekday_on_or_after(today, wd))
+}
+
+fn parse_japanese_day(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text {
+        "今日" => return Some(today),
+        "明日" => return Some(today + chrono::Duration::days(1)),
+        "明後日" => return Some(today + chrono::Duration::days(2)),
+        "来週" => return Some(next_week_day(today, Weekday::Mon)),
+        "来月" => return add_months(today.with_day(1)?, 1),
+        "月末" => return Some(clamp_day(today.year(), today.month(), 31)),
+        "昨日" => return sub_offset(today, 1, "d"),
+        "先週" => return sub_offset(today, 1, "w"),
+        "先月" => return sub_offset(today, 1, "m"),
+        _ => {}
+    }
+
+    // 偏移量：3日後、2週間後、1ヶ月後，或 3日前 等过去的日期
+    let (offset, past) = match (text.strip_suffix('後'), text.strip_suffix('前')) {
+        (Some(offset), _) => (Some(offset), false),
+        (_, Some(offset)) => (Some(offset), true),
+        _ => (None, false),
+    };
+    if let Some(offset) = offset {
+        let split_at = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (num, unit) = offset.split_at(split_at);
+        let unit = match unit {
+            "日" => "d",
+            "週間" | "週" => "w",
+            "ヶ月" | "か月" | "カ月" => "m",
+            _ => return None,
+        };
+        let num = num.parse().ok()?;
+        return if past { sub_offset(today, num, unit) } else { add_offset(today, num, unit) };
+    }
+
+    // 曜日：金曜日、金曜、来週月曜日
+    let weekday = |name: &str| {
+        let name = name.strip_suffix("曜日").or_else(|| name.strip_suffix("曜"))?;
+        match name {
+            "月" => Some(Weekday::Mon),
+            "火" => Some(Weekday::Tue),
+            "水" => Some(Weekday::Wed),
+            "木" => Some(Weekday::Thu),
+            "金" => Some(Weekday::Fri),
+            "土" => Some(Weekday::Sat),
+            "日" => Some(Weekday::Sun),
+            _ => None,
+        }
+    };
+    if let Some(day) = text.strip_prefix("来週") {
+        return weekday(day.trim()).map(|wd| next_week_day(today, wd));
+    }
+    weekday(text).map(|wd| weekday_on_or_after(today, wd))
+}
+
+// 按单位增加日期，单位为 d/w/m 或对应的英文单词
+fn add_offset(date: NaiveDate, num: u32, unit: &str) -> Option<NaiveDate> {
+    match unit {
+        "d" | "day" | "days" => date.checked_add_days(chrono::Days::new(num.into())),
+        "w" | "week" | "weeks" => date.checked_add_days(chrono::Days::new(u64::from(num) * 7)),
+        "m" | "month" | "months" => add_months(date, num),
+        _ => None,
+    }
+}
+
+// 减去偏移量，用于 -3d、last week 等过去的日期
+fn sub_offset(date: NaiveDate, num: u32, unit: &str) -> Option<NaiveDate> {
+    match unit {
+        "d" | "day" | "days" => date.checked_sub_days(chrono::Days::new(num.into())),
+        "w" | "week" | "weeks" => date.checked_sub_days(chrono::Days::new(u64::from(num) * 7)),
+        "m" | "month" | "months" => date.checked_sub_months(Months::new(num)),
+        _ => None,
+    }
+}
+
+// 增加月份，超出目标月份天数时取月末
+fn add_months(date: NaiveDate, months: u32) -> Option<NaiveDate> {
+    date.checked_add_months(Months::new(months))
+}
+
+// 今天或之后最近的指定星期
+fn weekday_on_or_after(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + chrono::Duration::days(days.into())
+}
+
+// 下周（周一为一周的开始）的指定星期
+fn next_week_day(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let this_monday = today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+    this_monday + chrono::Duration::days(7 + i64::from(weekday.num_days_from_monday()))
+}
+
+// 解析时长，如 "30d"、"2w"、"12h"
+fn parse_duration(duration_str: &str) -> Option<chrono::Duration> {
+    let duration_str = duration_str.trim().to_lowercase();
+    let split_at = duration_str.find(|c: char| !c.is_ascii_digit())?;
+    let (num_str, unit) = duration_str.split_at(split_at);
+    let num: i64 = num_str.parse().ok()?;
+
+    // 超出范围的时长视为无效，而不是溢出崩溃
+    match unit {
+        "h" => chrono::Duration::try_hours(num),
+        "d" => chrono::Duration::try_days(num),
+        "w" => chrono::Duration::try_weeks(num),
+        _ => None,
+    }
 }
 
 // 添加日期范围解析函数 - 修改为解析日期序列而不是直接生成日期
@@ -1455,20 +6095,17 @@ fn parse_date_range(range_str: &str) -> Option<Vec<DateTime<Local>>> {
     
     // 收集日期范围内的所有日期
     let mut dates = Vec::new();
-    // 修复警告：移除未使用的可变变量
-    let start_date = start_date;
     
     // 处理跨年或月份大小问题
     // 如果结束日期的月份小于开始日期的月份，可能是跨年的情况
     if end_date.month() < start_date.month() {
         // 假设是下一年，这仅适用于跨年情况
         let next_year = start_date.year() + 1;
-        let next_year_date = DateTime::<Local>::from_naive_utc_and_offset(
+        let next_year_date = local_datetime(
             NaiveDate::from_ymd_opt(next_year, end_date.month(), end_date.day())
-                .unwrap_or_else(|| NaiveDate::from_ymd_opt(next_year, end_date.month(), 28).unwrap())
-                .and_hms_opt(0, 0, 0).unwrap(),
-            Local::now().offset().clone()
-        );
+                .unwrap_or_else(|| NaiveDate::from_ymd_opt(next_year, end_date.month(), 28).unwrap()),
+            end_date.time(),
+        )?;
         dates.push(start_date);
         dates.push(next_year_date);
         return Some(dates);
@@ -1485,7 +6122,7 @@ fn parse_date_range(range_str: &str) -> Option<Vec<DateTime<Local>>> {
     }
     
     // 无效范围
-    return None;
+    None
 }
 
 fn show_help() {
@@ -1495,10 +6132,16 @@ fn show_help() {
     // 基本视图概念说明
     println!("\n【{}】", t.views());
     println!("  jodo               {}", t.task_view());
+    println!("  jodo today         {}", t.view_today_cmd());
+    println!("  jodo week          {}", t.view_week_cmd());
+    println!("  jodo overdue       {}", t.view_overdue_cmd());
+    println!("  jodo upcoming 14   {}", t.view_upcoming_cmd());
     // 基本用法
     println!("\n{}", t.basic_usage());
     println!("  jodo \"{}\"              {}", t.example_task(), t.add_task());
     println!("  jodo \"{}\" -t 2023-12-31      {}", t.example_task(), t.add_task_with_date());
+    println!("  jodo \"{}\" -t \"tomorrow 9am\"  {}", t.example_task(), t.add_task_with_time());
+    println!("  jodo parse-date next fri   {}", t.parse_date_cmd());
     println!("  jodo -l                    {}", t.list_tasks_cmd()); 
     // 任务管理命令
     println!("\n{}", t.task_management());
@@ -1506,18 +6149,58 @@ fn show_help() {
     println!("  jodo -e 1 -t 2023-12-25          {}", t.edit_task_due_date());
     println!("  jodo -c 1                  {}", t.mark_task_completed());
     println!("  jodo -u 1                  {}", t.mark_task_incomplete());
+    println!("  jodo \"{}\" -p H             {}", t.example_task(), t.add_task_with_priority());
+    println!("  jodo -e 1 -p 2             {}", t.set_priority_cmd());
+    println!("  jodo \"{}\" -t 2023-12-31 --repeat weekly  {}", t.example_task(), t.add_repeating_task());
+    println!("  jodo add --parent 3 \"{}\"  {}", t.example_task(), t.add_subtask_cmd());
+    println!("  jodo done 3 --children     {}", t.done_with_subtasks_cmd());
+    println!("  jodo edit 1 --interactive  {}", t.edit_interactive_cmd());
+    println!("  jodo edit-all +work        {}", t.edit_all_cmd());
+    println!("  jodo note 1                {}", t.note_cmd());
+    println!("  jodo note 1 --append \"...\" {}", t.note_append_cmd());
+    println!("  jodo block 5 --on 3        {}", t.block_cmd());
+    println!("  jodo unblock 5 --on 3      {}", t.unblock_cmd());
     println!("  jodo --star 1              {}", t.star_task_cmd());
     println!("  jodo --unstar 1            {}", t.unstar_task_cmd());
     println!("  jodo -d 1                  {}", t.delete_task());
     println!("  jodo --show 1              {}", t.show_task_details());
+    println!("  jodo tag 1 +work -home     {}", t.tag_cmd());
+    println!("  jodo list +work -blocked   {}", t.filter_by_tag_cmd());
+    println!("  jodo list \"due:<fri and not starred\"  {}", t.filter_expression_cmd());
+    println!("  jodo list --overdue        {}", t.filter_overdue_cmd());
+    println!("  jodo list --completed --since last-week  {}", t.completed_since_cmd());
+    println!("  jodo list --sort due,-priority  {}", t.sort_list_cmd());
+    println!("  jodo default-sort urgency  {}", t.default_sort_cmd());
+    println!("  jodo -l --format json      {}", t.json_output_cmd());
+    println!("  jodo undo-last             {}", t.undo_last_cmd());
+    println!("  jodo redo                  {}", t.redo_cmd());
+    println!("  jodo log                   {}", t.log_cmd());
+    println!("  jodo stats                 {}", t.stats_cmd());
+    println!("  jodo search report         {}", t.search_cmd());
+    println!("  jodo search --regex \"^fix\" {}", t.search_regex_cmd());
+    println!("  jodo view save standup --sort due due:<=today  {}", t.view_save_cmd());
+    println!("  jodo view standup          {}", t.view_show_cmd());
+    println!("  jodo view list / rm <name> {}", t.view_manage_cmd());
+    println!("  jodo trash                 {}", t.trash_cmd());
+    println!("  jodo restore 1             {}", t.restore_cmd());
+    println!("  jodo purge --older-than 30d {}", t.purge_cmd());
+    println!("  jodo export -o todo.txt    {}", t.export_cmd());
+    println!("  jodo archive --older-than 14d  {}", t.archive_cmd());
+    println!("  jodo auto-archive 30d      {}", t.auto_archive_cmd());
+    println!("  jodo list --archived       {}", t.list_archived_cmd());
     println!("  jodo -m                     {}", t.batch_mode());    
     // 其他选项
     println!("\n{}", t.other_options());
+    println!("  jodo lists                 {}", t.list_lists_cmd());
+    println!("  jodo switch <name>         {}", t.switch_list_cmd());
+    println!("  jodo --list-name <name>    {}", t.use_list_cmd());
+    println!("  jodo --data-dir <dir>      {}", t.data_dir_cmd());
     println!("  jodo -L <lang>             {}", t.set_language());
     println!("  jodo -h, --help              {}", t.show_help_info());
     println!("  jodo -v, --version           {}", t.show_version_info());
     // 注意事项
     println!("\n{}", t.note_completed_tasks());
+    println!("{}", t.note_uid());
     // 示例
     println!("\n{}", t.examples());
     println!("  jodo \"{}\"", t.example_task());
@@ -1596,6 +6279,50 @@ fn parse_id_range(id_str: &str) -> Vec<String> {
     vec![id_str.to_string()]
 }
 
+// 保存、列出或删除自定义视图
+fn manage_views(config: &mut Config, action: &str, name: Option<&str>, filter: &[String], sort: Option<&str>, columns: Option<&str>) -> Result<(), String> {
+    let t = get_translations();
+
+    if action == "list" {
+        println!("{}", t.saved_views());
+        if config.views.is_empty() {
+            println!("  {}", t.none().italic());
+        }
+        for (name, view) in &config.views {
+            let columns: Vec<&str> = view.columns.iter().map(|c| c.name()).collect();
+            println!("  {:<12} {}", name.green(), view.filter.join(" "));
+            println!("  {:<12} --sort {} --columns {}", "", if view.sort.is_empty() { "id" } else { &view.sort }, columns.join(","));
+        }
+        return Ok(());
+    }
+
+    let name = name.ok_or_else(|| t.view_name_required())?;
+    if action == "rm" {
+        config.views.remove(name).ok_or_else(|| t.view_not_found(name))?;
+        config.save().map_err(|e| e.to_string())?;
+        println!("{}", t.view_removed(name));
+        return Ok(());
+    }
+
+    // 视图名称不能与 view 的子命令相同
+    if !is_valid_list_name(name) || ["save", "list", "rm"].contains(&name) {
+        return Err(t.invalid_view_name(name));
+    }
+    // 保存前先检查过滤表达式、排序方式和列是否有效
+    TaskFilter::from_args(filter)?;
+    let sort = sort.unwrap_or_default().to_string();
+    parse_sort(&sort)?;
+    let columns = match columns {
+        Some(spec) => parse_columns(spec)?,
+        None => default_columns(),
+    };
+
+    config.views.insert(name.to_string(), SavedView { filter: filter.to_vec(), sort, columns });
+    config.save().map_err(|e| e.to_string())?;
+    println!("{}", t.view_saved(name));
+    Ok(())
+}
+
 // 扩展ID解析，支持范围
 fn expand_id_ranges(id_strs: &[String]) -> Vec<String> {
     let mut expanded_ids = Vec::new();
@@ -1608,6 +6335,21 @@ fn expand_id_ranges(id_strs: &[String]) -> Vec<String> {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    // 数据目录决定了配置文件的位置，需要在加载配置之前解析
+    for (i, arg) in args.iter().enumerate() {
+        let dir = if arg == "--data-dir" {
+            args.get(i + 1).map(|d| d.as_str())
+        } else {
+            arg.strip_prefix("--data-dir=")
+        };
+        if let Some(dir) = dir {
+            let _ = DATA_DIR_OVERRIDE.set(PathBuf::from(dir));
+            break;
+        }
+    }
+
     // 首先加载配置
     let mut config = Config::load();
     
@@ -1618,7 +6360,6 @@ fn main() {
     
     // 特殊处理语言切换: 检查是否只有语言切换参数
     // 如果是，则更新配置并显示成功消息
-    let args: Vec<String> = std::env::args().collect();
     if args.len() == 3 && (args[1] == "-L" || args[1] == "--language") {
         let lang_str = &args[2];
         let new_language = Language::from_str(lang_str);
@@ -1672,9 +6413,15 @@ fn main() {
     cli.list = matches.list;
     cli.show_id = matches.show_id;
     cli.multi_mode = matches.multi_mode;    
+    cli.priority = matches.priority;
+    cli.repeat = matches.repeat;
+    cli.list_name = matches.list_name;
+    cli.data_dir = matches.data_dir;
+    cli.format = matches.format;
     cli.command = matches.command;
 
     let t = get_translations();
+    let format = cli.format;
     
     // 处理帮助选项
     if cli.help || (cli.command.is_some() && matches!(cli.command.as_ref().unwrap(), Commands::Help)) {
@@ -1687,33 +6434,142 @@ fn main() {
         show_version();
         return;
     }
-    
-    // 更新语言设置 (从命令行参数)
-    if let Some(lang_str) = &cli.language {
-        let new_language = Language::from_str(lang_str);
-        unsafe {
-            CURRENT_LANGUAGE = new_language;
-        }
-        
-        // 更新并保存配置
-        config.language = new_language;
-        if let Err(e) = config.save() {
-            eprintln!("{}", t.error(&format!("无法保存语言设置: {}", e)));
-        }
-        
-        // 语言切换后立即显示帮助
-        show_help();
+    
+    // 更新语言设置 (从命令行参数)
+    if let Some(lang_str) = &cli.language {
+        let new_language = Language::from_str(lang_str);
+        unsafe {
+            CURRENT_LANGUAGE = new_language;
+        }
+        
+        // 更新并保存配置
+        config.language = new_language;
+        if let Err(e) = config.save() {
+            eprintln!("{}", t.error(&format!("无法保存语言设置: {}", e)));
+        }
+        
+        // 语言切换后立即显示帮助
+        show_help();
+        return;
+    }
+
+    // 处理任务列表管理命令，这些命令不需要加载任务
+    match &cli.command {
+        Some(Commands::Lists) => {
+            println!("{}", t.task_lists());
+            for name in available_lists() {
+                if name == config.current_list {
+                    println!("* {}", name.green().bold());
+                } else {
+                    println!("  {}", name);
+                }
+            }
+            return;
+        },
+        Some(Commands::Switch { name }) => {
+            if !is_valid_list_name(name) {
+                eprintln!("{}", t.error(&t.invalid_list_name(name)));
+                return;
+            }
+            config.current_list = name.clone();
+            if let Err(e) = config.save() {
+                eprintln!("{}", t.error(&e.to_string()));
+                return;
+            }
+            println!("{}", t.list_switched(name));
+            return;
+        },
+        Some(Commands::View { action, name, filters, sort, columns }) if ["save", "list", "rm"].contains(&action.as_str()) => {
+            if let Err(e) = manage_views(&mut config, action, name.as_deref(), filters, sort.as_deref(), columns.as_deref()) {
+                eprintln!("{}", t.error(&e));
+            }
+            return;
+        },
+        Some(Commands::DefaultSort { keys }) => {
+            match keys {
+                Some(keys) => {
+                    if let Err(e) = parse_sort(keys) {
+                        eprintln!("{}", t.error(&e));
+                        return;
+                    }
+                    // priority 与未设置时的默认排序相同
+                    config.sort = if keys.trim() == "priority" { String::new() } else { keys.trim().to_string() };
+                    if let Err(e) = config.save() {
+                        eprintln!("{}", t.error(&e.to_string()));
+                        return;
+                    }
+                    println!("{}", t.default_sort_set(keys.trim()));
+                },
+                None => println!("{}", t.default_sort_is(if config.sort.is_empty() { "priority" } else { &config.sort })),
+            }
+            return;
+        },
+        Some(Commands::AutoArchive { duration }) => {
+            match duration.as_deref().map(str::trim) {
+                Some("off") => {
+                    config.auto_archive = String::new();
+                    if let Err(e) = config.save() {
+                        eprintln!("{}", t.error(&e.to_string()));
+                        return;
+                    }
+                    println!("{}", t.auto_archive_off());
+                },
+                Some(duration) => {
+                    if parse_duration(duration).is_none() {
+                        eprintln!("{}", t.error(t.invalid_duration()));
+                        return;
+                    }
+                    config.auto_archive = duration.to_string();
+                    if let Err(e) = config.save() {
+                        eprintln!("{}", t.error(&e.to_string()));
+                        return;
+                    }
+                    println!("{}", t.auto_archive_set(duration));
+                },
+                None if config.auto_archive.is_empty() => println!("{}", t.auto_archive_off()),
+                None => println!("{}", t.auto_archive_set(&config.auto_archive)),
+            }
+            return;
+        },
+        Some(Commands::ParseDate { text }) => {
+            let text = text.join(" ");
+            match parse_date(&text) {
+                Ok(date) => println!("{} → {} ({})", text, format_due(&date), t.weekday_name(date.weekday())),
+                Err(e) => eprintln!("{}", t.error(e)),
+            }
+            return;
+        },
+        _ => {}
+    }
+
+    // 配置中的默认排序方式，无效时使用内置的排序
+    let default_sort = parse_sort(&config.sort).unwrap_or_default();
+
+    // 确定本次使用的任务列表：--list-name 优先，否则使用配置中的当前列表
+    let list_name = cli.list_name.clone().unwrap_or_else(|| config.current_list.clone());
+    if !is_valid_list_name(&list_name) {
+        eprintln!("{}", t.error(&t.invalid_list_name(&list_name)));
         return;
     }
 
     // 初始化TodoList实例
-    let mut todo_list = match TodoList::new() {
+    let mut todo_list = match TodoList::new(&list_name) {
         Ok(list) => list,
         Err(e) => {
             eprintln!("{}", t.init_failed(&e.to_string()));
             return;
         }
     };
+
+    // 自动归档只在添加任务前执行：查看等只读命令不应改变刚显示过的ID，也不应写入操作日志
+    if let Some(age) = parse_duration(&config.auto_archive) {
+        let adding = (cli.task.is_some() && cli.edit_id.is_none()) || cli.multi_mode || matches!(cli.command, Some(Commands::Add { .. }));
+        if adding {
+            if let Err(e) = todo_list.archive_tasks(Some(age)) {
+                eprintln!("{}", t.error(e));
+            }
+        }
+    }
     
     // 处理添加新任务
     if let Some(ref task_str) = cli.task {
@@ -1721,26 +6577,7 @@ fn main() {
         if cli.edit_id.is_some() {
             // 编辑模式下不执行添加任务逻辑
         } else {
-            // 添加新任务
-            let due_date = match &cli.due_date {
-                Some(date_str) => match parse_date(date_str) {
-                    Ok(date) => Some(date),
-                    Err(e) => {
-                        eprintln!("{}", t.error(e));   
-                        return;      
-                    }
-                },  
-                None => None,        
-            };
-        
-            if let Err(e) = todo_list.add_task(task_str.clone(), due_date) {
-                eprintln!("{}", t.error(&e.to_string()));
-            } else {
-                println!("{}", t.task_added(&task_str));
-                if due_date.is_some() {
-                    println!("{}: {}", t.due_date(), cli.due_date.as_ref().unwrap());       
-                }
-            }   
+            add_from_args(&mut todo_list, format, task_str, cli.due_date.as_deref(), cli.priority, cli.repeat.as_deref(), cli.parent.as_deref());
             return;        
         }
     }
@@ -1750,9 +6587,8 @@ fn main() {
         // 检测批量编辑格式
         if id_str.contains("to") || id_str.contains('-') {
             // 解析ID范围
-            let parts: Vec<&str>;
             let delimiter = if id_str.contains("to") { "to" } else { "-" };
-            parts = id_str.split(delimiter).collect();
+            let parts: Vec<&str> = id_str.split(delimiter).collect();
             
             if parts.len() == 2 && 
                parts[0].parse::<usize>().is_ok() && 
@@ -1778,9 +6614,9 @@ fn main() {
                         match todo_list.edit_tasks_due_date(&ids, due_date) {
                             Ok(updated_ids) => {
                                 if updated_ids.len() == 1 {
-                                    println!("{}", t.due_date_updated(&updated_ids[0]));
+                                    print_result(&todo_list, format, &t.due_date_updated(&updated_ids[0]));
                                 } else {
-                                    println!("已更新任务 {} 的截止日期", updated_ids.join(", "));
+                                    print_result(&todo_list, format, &format!("已更新任务 {} 的截止日期", updated_ids.join(", ")));
                                 }
                                 return;
                             },
@@ -1797,8 +6633,8 @@ fn main() {
         // 单任务编辑处理
         // 按优先级确定编辑内容: 编辑参数 > 主参数 > 内容选项
         let desc = cli.edit_content_arg.as_deref()
-            .or_else(|| cli.task.as_deref())
-            .or_else(|| cli.edit_content.as_deref());
+            .or(cli.task.as_deref())
+            .or(cli.edit_content.as_deref());
         
         // 解析截止日期
         let due_date = match &cli.due_date {
@@ -1812,14 +6648,23 @@ fn main() {
             None => None,    
         };
         
+        let repeat = match cli.repeat.as_deref().map(parse_repeat).transpose() {
+            Ok(repeat) => repeat,
+            Err(e) => {
+                eprintln!("{}", t.error(&e));
+                return;
+            }
+        };
+        
         // 验证编辑参数存在
-        if desc.is_none() && due_date.is_none() {
+        if desc.is_none() && due_date.is_none() && cli.priority.is_none() && repeat.is_none() {
             eprintln!("{}", t.error(&t.provide_content_or_date()));   
             return;
         }
             
         // 执行编辑操作
-        match todo_list.edit_task(&id_str, desc, due_date) {
+        match todo_list.edit_task(&id_str, desc, due_date, cli.priority, repeat.clone()) {
+            Ok(_) if format != OutputFormat::Text => print_result(&todo_list, format, ""),
             Ok(_) => {
                 if desc.is_some() {
                     println!("{}", t.content_updated(&id_str));
@@ -1827,6 +6672,12 @@ fn main() {
                 if due_date.is_some() {
                     println!("{}", t.due_date_updated(&id_str));      
                 }
+                if let Some(priority) = cli.priority {
+                    println!("{}", t.priority_updated(&id_str, &t.priority_name(priority)));
+                }
+                if repeat.is_some() {
+                    println!("{}", t.repeat_updated(&id_str));
+                }
             },   
             Err(e) => eprintln!("{}", t.error(e)),
         }
@@ -1838,13 +6689,15 @@ fn main() {
         // 扩展可能的ID范围
         let expanded_ids = expand_id_ranges(&cli.complete_ids);
         
+        let warnings = todo_list.open_dependency_warnings(&expanded_ids);
         match todo_list.mark_done_multiple(&expanded_ids) {
             Ok(ids) => {
                 if ids.len() == 1 {
-                    println!("{}", t.task_completed(&ids[0]));
+                    print_result(&todo_list, format, &t.task_completed(&ids[0]));
                 } else { 
-                    println!("{}", t.tasks_completed(&ids));      
+                    print_result(&todo_list, format, &t.tasks_completed(&ids));      
                 }
+                warnings.iter().for_each(|warning| eprintln!("{}", warning.yellow()));
             },   
             Err(e) => eprintln!("{}", t.error(e)),
         }   
@@ -1859,7 +6712,7 @@ fn main() {
         if expanded_ids.len() == 1 {
             // 单个ID的情况，保持原有行为
             match todo_list.mark_undone(&id_str) {
-                Ok(_) => println!("{}", t.task_uncompleted(&id_str)),   
+                Ok(_) => print_result(&todo_list, format, &t.task_uncompleted(&id_str)),   
                 Err(e) => eprintln!("{}", t.error(e)),
             }
         } else {
@@ -1867,9 +6720,9 @@ fn main() {
             match todo_list.mark_undone_multiple(&expanded_ids) {
                 Ok(ids) => {
                     if ids.len() == 1 {
-                        println!("{}", t.task_uncompleted(&ids[0]));
+                        print_result(&todo_list, format, &t.task_uncompleted(&ids[0]));
                     } else {
-                        println!("{}", t.tasks_uncompleted(&ids));
+                        print_result(&todo_list, format, &t.tasks_uncompleted(&ids));
                     }
                 },
                 Err(e) => eprintln!("{}", t.error(e)),
@@ -1881,7 +6734,7 @@ fn main() {
     // 处理标记重要任务
     if let Some(id_str) = cli.star_id {
         match todo_list.star_task(&id_str) {
-            Ok(_) => println!("{}", t.task_starred(&id_str)),   
+            Ok(_) => print_result(&todo_list, format, &t.task_starred(&id_str)),   
             Err(e) => eprintln!("{}", t.error(e)),
         }   
         return;    
@@ -1890,7 +6743,7 @@ fn main() {
     // 处理取消重要标记
     if let Some(id_str) = cli.unstar_id {
         match todo_list.unstar_task(&id_str) {
-            Ok(_) => println!("{}", t.task_unstarred(&id_str)),   
+            Ok(_) => print_result(&todo_list, format, &t.task_unstarred(&id_str)),   
             Err(e) => eprintln!("{}", t.error(e)),
         }   
         return;    
@@ -1905,14 +6758,16 @@ fn main() {
         match todo_list.remove_tasks(&expanded_ids) {
             Ok(ids) => {
                 if ids.len() == 1 {
-                    println!("{}", t.task_deleted(&ids[0].to_string()));
+                    print_result(&todo_list, format, &t.task_deleted(&ids[0].to_string()));
                 } else {
-                    println!("{}", t.tasks_deleted(&ids));    
+                    print_result(&todo_list, format, &t.tasks_deleted(&ids));    
                 }
                 
                 // 显示当前任务列表，以便用户看到删除后的结果
-                println!();  
-                todo_list.list_tasks();
+                if format == OutputFormat::Text {
+                    println!();  
+                    todo_list.list_tasks(&TaskFilter::default(), &default_sort);
+                }
             },   
             Err(e) => eprintln!("{}", t.error(e)),
         }   
@@ -1921,7 +6776,7 @@ fn main() {
     
     // 处理显示详细信息
     if let Some(id_str) = cli.show_id {
-        match todo_list.show_task_detail(&id_str) {
+        match todo_list.show_task_detail(&id_str, format) {
             Ok(_) => {},
             Err(e) => eprintln!("{}", t.error(e)),
         }   
@@ -1956,7 +6811,7 @@ fn main() {
                 println!("日期范围模式：从 {} 到 {}", start_date, end_date);
                 
                 // 计算任务数量
-                let days_diff = (dates[1] - dates[0]).num_days().abs() as usize + 1;
+                let days_diff = (dates[1] - dates[0]).num_days().unsigned_abs() as usize + 1;
                 println!("将创建 {} 个任务，每个任务依次对应从开始到结束日期", days_diff);
             }
         }
@@ -2022,9 +6877,9 @@ fn main() {
                     let end_date = dates[1];
                     
                     if start_date <= end_date {
-                        // 常规情况：按天递增
+                        // 常规情况：按天递增（按日历日计算，避免夏令时切换时偏移）
                         let days_to_add = date_index as i64;
-                        Some(start_date + chrono::Duration::days(days_to_add))
+                        local_datetime(start_date.date_naive() + chrono::Duration::days(days_to_add), start_date.time())
                     } else if date_index == 0 {
                         // 跨年情况：第一个任务使用开始日期
                         Some(start_date)
@@ -2045,14 +6900,15 @@ fn main() {
             };
             
             // 添加任务
-            if let Err(e) = todo_list.add_task(input.to_string(), due_date) {
+            let (description, tags) = extract_tags(input);
+            if let Err(e) = todo_list.add_task(description.clone(), due_date, tags, cli.priority.unwrap_or_default(), None, None) {
                 eprintln!("{}", t.error(&e.to_string()));
             } else { 
                 if let Some(date) = due_date {
-                    let date_str = date.format("%Y-%m-%d").to_string();
-                    println!("{} ({})", t.task_added(input), date_str);
+                    let date_str = format_due(&date);
+                    println!("{} ({})", t.task_added(&description), date_str);
                 } else {
-                    println!("{}", t.task_added(input));
+                    println!("{}", t.task_added(&description));
                 }
                 
                 // 增加日期索引
@@ -2064,32 +6920,163 @@ fn main() {
     
     // 处理列出所有任务
     if cli.list || cli.command.is_none() {
-        todo_list.list_tasks();   
+        todo_list.print_list(format, &TaskFilter::default(), &default_sort);   
         return;    
     }
     
     // 处理子命令
     match cli.command {
-        Some(Commands::List) => todo_list.list_tasks(),
-        Some(Commands::Done { id }) => {
-            match todo_list.mark_done(&id) {
-                Ok(_) => println!("{}", t.task_completed(&id)),
+        Some(Commands::List { filters, due_before, due_after, created_since, since, search, overdue, starred, completed, sort, archived }) => {
+            let sort = match sort.as_deref().map(parse_sort).transpose() {
+                Ok(sort) => sort.unwrap_or(default_sort),
+                Err(e) => {
+                    eprintln!("{}", t.cmd_error(&e));
+                    return;
+                }
+            };
+            let filter = TaskFilter::from_args(&filters).and_then(|mut filter| {
+                let options = [
+                    ("--due-before", due_before),
+                    ("--due-after", due_after),
+                    ("--created-since", created_since),
+                    ("--since", since),
+                    ("--search", search),
+                ];
+                for (option, value) in options {
+                    if value.is_some() {
+                        filter.add_option(option, value.as_deref())?;
+                    }
+                }
+                let flags = [
+                    (overdue, Condition::Overdue),
+                    (starred, Condition::Starred),
+                    (completed, Condition::Completed),
+                ];
+                filter.conditions.extend(flags.into_iter().filter(|(set, _)| *set).map(|(_, c)| c));
+                Ok(filter)
+            });
+            let filter = match filter {
+                Ok(filter) => filter,
+                Err(e) => {
+                    eprintln!("{}", t.cmd_error(&e));
+                    return;
+                }
+            };
+            if !archived {
+                todo_list.print_list(format, &filter, &sort);
+                return;
+            }
+            match todo_list.archived() {
+                Ok(archive) => {
+                    if format == OutputFormat::Text {
+                        println!("{}", t.archived_tasks().dimmed());
+                    }
+                    archive.print_list(format, &filter, &sort);
+                },
+                Err(e) => eprintln!("{}", t.cmd_error(&e.to_string())),
+            }
+        },
+        Some(Commands::Note { id, append }) => {
+            let task = match todo_list.get_task(&id) {
+                Ok(task) => task,
+                Err(e) => {
+                    eprintln!("{}", t.cmd_error(e));
+                    return;
+                }
+            };
+            let (uid, current) = (task.uid, task.notes.clone());
+
+            let notes = match append {
+                Some(text) if current.is_empty() => text,
+                Some(text) => format!("{}\n{}", current, text),
+                None => match edit_in_editor(format!("{}\n", current).trim_start(), "jodo-note", "md") {
+                    Ok(edited) => edited.trim_end().to_string(),
+                    Err(e) => {
+                        eprintln!("{}", t.error(&e.to_string()));
+                        return;
+                    }
+                },
+            };
+            if notes == current {
+                println!("{}", t.notes_unchanged(&id));
+                return;
+            }
+
+            match todo_list.set_notes(uid, notes) {
+                Ok(_) => print_result(&todo_list, format, &t.notes_updated(&id)),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Block { id, on }) => {
+            match todo_list.block_task(&id, &on) {
+                Ok(_) => print_result(&todo_list, format, &t.dependency_added(&id, &on.join(", "))),
+                Err(e) => eprintln!("{}", t.cmd_error(&e)),
+            }
+        },
+        Some(Commands::Unblock { id, on }) => {
+            match todo_list.unblock_task(&id, &on) {
+                Ok(_) => print_result(&todo_list, format, &t.dependency_removed(&id, &on.join(", "))),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Tag { id, changes }) => {
+            match todo_list.tag_task(&id, &changes) {
+                Ok(_) => print_result(&todo_list, format, &t.tags_updated(&id)),
+                Err(e) => eprintln!("{}", t.cmd_error(&e)),
+            }
+        },
+        Some(Commands::Add { content, due_date, priority, repeat, parent }) => {
+            add_from_args(&mut todo_list, format, &content, due_date.as_deref(), priority, repeat.as_deref(), parent.as_deref());
+        },
+        Some(Commands::Done { id, children }) => {
+            let warnings = todo_list.open_dependency_warnings(std::slice::from_ref(&id));
+            match todo_list.mark_done(&id, children) {
+                Ok(_) => {
+                    print_result(&todo_list, format, &t.task_completed(&id));
+                    warnings.iter().for_each(|warning| eprintln!("{}", warning.yellow()));
+                },
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
         Some(Commands::Undo { id }) => {
             match todo_list.mark_undone(&id) {
-                Ok(_) => println!("{}", t.task_uncompleted(&id)),
+                Ok(_) => print_result(&todo_list, format, &t.task_uncompleted(&id)),
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
         Some(Commands::Remove { id }) => {
             match todo_list.remove_task(&id) {
-                Ok(_) => println!("{}", t.task_deleted(&id)),
+                Ok(_) => print_result(&todo_list, format, &t.task_deleted(&id)),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Edit { id, interactive: true, .. }) => {
+            match todo_list.get_task(&id) {
+                Ok(task) => {
+                    let uid = task.uid;
+                    edit_tasks_in_editor(&mut todo_list, format, &[uid]);
+                },
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
-        Some(Commands::Edit { id, content, time }) => {
+        Some(Commands::EditAll { filters }) => {
+            match TaskFilter::from_args(&filters) {
+                Ok(filter) => {
+                    // 按显示顺序排列，子任务紧跟在父任务之后
+                    let mut tasks: Vec<&Task> = todo_list.tasks.iter()
+                        .filter(|task| !task.deleted && filter.matches(task))
+                        .collect();
+                    tasks.sort_by_key(|task| match task.parent_id {
+                        0 => (task.id, 0),
+                        parent_id => (parent_id, task.id),
+                    });
+                    let uids: Vec<Uuid> = tasks.iter().map(|task| task.uid).collect();
+                    edit_tasks_in_editor(&mut todo_list, format, &uids);
+                },
+                Err(e) => eprintln!("{}", t.cmd_error(&e)),
+            }
+        },
+        Some(Commands::Edit { id, content, time, priority, repeat, .. }) => {
             let desc = content.as_deref();
             
             let due_date = match time {
@@ -2103,7 +7090,16 @@ fn main() {
                 None => None,
             };
             
-            match todo_list.edit_task(&id, desc, due_date) {
+            let repeat = match repeat.as_deref().map(parse_repeat).transpose() {
+                Ok(repeat) => repeat,
+                Err(e) => {
+                    eprintln!("{}", t.cmd_error(&e));
+                    return;
+                }
+            };
+
+            match todo_list.edit_task(&id, desc, due_date, priority, repeat.clone()) {
+                Ok(_) if format != OutputFormat::Text => print_result(&todo_list, format, ""),
                 Ok(_) => {
                     if desc.is_some() {
                         println!("{}", t.content_updated(&id));
@@ -2111,32 +7107,648 @@ fn main() {
                     if due_date.is_some() {
                         println!("{}", t.due_date_updated(&id));      
                     }
+                    if let Some(priority) = priority {
+                        println!("{}", t.priority_updated(&id, &t.priority_name(priority)));
+                    }
+                    if repeat.is_some() {
+                        println!("{}", t.repeat_updated(&id));
+                    }
                 },   
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
         Some(Commands::Star { id }) => {
             match todo_list.star_task(&id) {
-                Ok(_) => println!("{}", t.task_starred(&id)),
+                Ok(_) => print_result(&todo_list, format, &t.task_starred(&id)),
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
         Some(Commands::Unstar { id }) => {
             match todo_list.unstar_task(&id) {
-                Ok(_) => println!("{}", t.task_unstarred(&id)),
+                Ok(_) => print_result(&todo_list, format, &t.task_unstarred(&id)),
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },
         Some(Commands::Show { id }) => {
-            match todo_list.show_task_detail(&id) {
+            match todo_list.show_task_detail(&id, format) {
                 Ok(_) => {},
                 Err(e) => eprintln!("{}", t.cmd_error(e)),
             }
         },   
         Some(Commands::Help) => {       
-            show_help();        
-            return;
+            show_help();
+        },
+        Some(Commands::UndoLast) => {
+            match todo_list.undo_last() {
+                Ok(operation) => print_result(&todo_list, format, &t.operation_undone(&t.operation_name(operation))),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Redo) => {
+            match todo_list.redo() {
+                Ok(operation) => print_result(&todo_list, format, &t.operation_redone(&t.operation_name(operation))),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Log) => todo_list.show_log(),
+        Some(Commands::Search { pattern, regex, fuzzy, trash, archived }) => {
+            let matcher = match Matcher::new(&pattern, regex, fuzzy) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    eprintln!("{}", t.cmd_error(&e));
+                    return;
+                }
+            };
+            if !archived {
+                todo_list.search(&pattern, &matcher, trash, format);
+                return;
+            }
+            match todo_list.archived() {
+                Ok(archive) => {
+                    if format == OutputFormat::Text {
+                        println!("{}", t.archived_tasks().dimmed());
+                    }
+                    archive.search(&pattern, &matcher, false, format);
+                },
+                Err(e) => eprintln!("{}", t.cmd_error(&e.to_string())),
+            }
+        },
+        Some(Commands::View { action, .. }) => {
+            match config.views.get(&action) {
+                Some(view) => {
+                    if let Err(e) = todo_list.show_view(view, format) {
+                        eprintln!("{}", t.cmd_error(&e));
+                    }
+                },
+                None => eprintln!("{}", t.cmd_error(&t.view_not_found(&action))),
+            }
+        },
+        Some(Commands::Today) => todo_list.show_agenda(AgendaView::Today, format),
+        Some(Commands::Week) => todo_list.show_agenda(AgendaView::Week, format),
+        Some(Commands::Overdue) => todo_list.show_agenda(AgendaView::Overdue, format),
+        Some(Commands::Upcoming { days }) => todo_list.show_agenda(AgendaView::Upcoming(days), format),
+        Some(Commands::Stats { days, weeks }) => todo_list.show_stats(days, weeks),
+        Some(Commands::Trash) => todo_list.list_trash(),
+        Some(Commands::Restore { ids }) => {
+            let expanded_ids = expand_id_ranges(&ids);
+            match todo_list.restore_tasks(&expanded_ids) {
+                Ok(ids) => print_result(&todo_list, format, &t.tasks_restored(&ids)),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Purge { older_than }) => {
+            let older_than = match older_than {
+                Some(duration_str) => match parse_duration(&duration_str) {
+                    Some(duration) => Some(duration),
+                    None => {
+                        eprintln!("{}", t.cmd_error(t.invalid_duration()));
+                        return;
+                    }
+                },
+                None => None,
+            };
+            match todo_list.purge_trash(older_than) {
+                Ok(count) => print_result(&todo_list, format, &t.tasks_purged(count)),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
+        },
+        Some(Commands::Export { output, archived }) => {
+            // 默认的 text 格式导出为 todo.txt
+            let format = if format == OutputFormat::Text { OutputFormat::Todotxt } else { format };
+            let archive;
+            let source = if archived {
+                archive = match todo_list.archived() {
+                    Ok(archive) => archive,
+                    Err(e) => {
+                        eprintln!("{}", t.cmd_error(&e.to_string()));
+                        return;
+                    }
+                };
+                &archive
+            } else {
+                &todo_list
+            };
+
+            let tasks = source.export_order();
+            let contents = render_tasks(&tasks, format);
+            match output {
+                Some(path) => match write_atomic(&path, contents.as_bytes()) {
+                    Ok(()) => println!("{}", t.tasks_exported(tasks.len(), &path.display().to_string())),
+                    Err(e) => eprintln!("{}", t.cmd_error(&e.to_string())),
+                },
+                None => print!("{}", contents),
+            }
+        },
+        Some(Commands::Archive { older_than }) => {
+            let older_than = match older_than {
+                Some(duration_str) => match parse_duration(&duration_str) {
+                    Some(duration) => Some(duration),
+                    None => {
+                        eprintln!("{}", t.cmd_error(t.invalid_duration()));
+                        return;
+                    }
+                },
+                None => None,
+            };
+            match todo_list.archive_tasks(older_than) {
+                Ok(count) => print_result(&todo_list, format, &t.tasks_archived(count)),
+                Err(e) => eprintln!("{}", t.cmd_error(e)),
+            }
         },
+        // 列表管理命令已在加载任务之前处理
+        Some(Commands::Lists) | Some(Commands::Switch { .. }) | Some(Commands::ParseDate { .. }) | Some(Commands::DefaultSort { .. }) | Some(Commands::AutoArchive { .. }) => {},
         None => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn task(description: &str) -> Task {
+        Task {
+            id: 1,
+            uid: Uuid::new_v4(),
+            description: description.to_string(),
+            completed: false,
+            created_at: Local::now(),
+            due_date: None,
+            local_due: true,
+            starred: false,
+            deleted: false,
+            deleted_at: None,
+            tags: Vec::new(),
+            priority: Priority::None,
+            repeat: None,
+            parent: None,
+            parent_id: 0,
+            depends_on: Vec::new(),
+            notes: String::new(),
+            completed_at: None,
+        }
+    }
+
+    fn filter(expression: &str) -> Result<TaskFilter, String> {
+        TaskFilter::from_args(&[expression.to_string()])
+    }
+
+    // 相对日期（2026-10-19 是星期一）
+
+    #[test]
+    fn weekday_on_or_after_includes_today() {
+        let monday = date(2026, 10, 19);
+        assert_eq!(weekday_on_or_after(monday, Weekday::Mon), monday);
+        assert_eq!(weekday_on_or_after(monday, Weekday::Fri), date(2026, 10, 23));
+        assert_eq!(weekday_on_or_after(date(2026, 10, 24), Weekday::Mon), date(2026, 10, 26));
+    }
+
+    #[test]
+    fn next_week_day_is_in_the_following_week() {
+        let monday = date(2026, 10, 19);
+        assert_eq!(next_week_day(monday, Weekday::Mon), date(2026, 10, 26));
+        assert_eq!(next_week_day(date(2026, 10, 25), Weekday::Mon), date(2026, 10, 26));
+        assert_eq!(next_week_day(date(2026, 10, 21), Weekday::Sun), date(2026, 11, 1));
+    }
+
+    #[test]
+    fn english_expressions() {
+        let monday = date(2026, 10, 19);
+        let parse = |text| parse_relative_day_in(text, monday, Language::English);
+        assert_eq!(parse("monday"), Some(monday));
+        assert_eq!(parse("next monday"), Some(date(2026, 10, 26)));
+        assert_eq!(parse("Next  FRI"), Some(date(2026, 10, 30)));
+        assert_eq!(parse("next week"), Some(date(2026, 10, 26)));
+        assert_eq!(parse("next month"), Some(date(2026, 11, 1)));
+        assert_eq!(parse("+3d"), Some(date(2026, 10, 22)));
+        assert_eq!(parse("in 2 weeks"), Some(date(2026, 11, 2)));
+        assert_eq!(parse("-1w"), Some(date(2026, 10, 12)));
+        assert_eq!(parse("3 days ago"), Some(date(2026, 10, 16)));
+        assert_eq!(parse("+3x"), None);
+        assert_eq!(parse("someday"), None);
+    }
+
+    #[test]
+    fn end_of_month_and_month_offsets_clamp() {
+        let parse = |text, today| parse_relative_day_in(text, today, Language::English);
+        assert_eq!(parse("end of month", date(2026, 2, 10)), Some(date(2026, 2, 28)));
+        assert_eq!(parse("eom", date(2028, 2, 10)), Some(date(2028, 2, 29)));
+        assert_eq!(parse("+1m", date(2026, 1, 31)), Some(date(2026, 2, 28)));
+        assert_eq!(parse("in 1 month", date(2026, 3, 31)), Some(date(2026, 4, 30)));
+        assert_eq!(parse("last month", date(2026, 3, 31)), Some(date(2026, 2, 28)));
+    }
+
+    #[test]
+    fn chinese_keywords_only_in_chinese() {
+        let monday = date(2026, 10, 19);
+        let parse = |text| parse_relative_day_in(text, monday, Language::Chinese);
+        assert_eq!(parse("明天"), Some(date(2026, 10, 20)));
+        assert_eq!(parse("下周一"), Some(date(2026, 10, 26)));
+        assert_eq!(parse("周五"), Some(date(2026, 10, 23)));
+        assert_eq!(parse("星期日"), Some(date(2026, 10, 25)));
+        assert_eq!(parse("3天后"), Some(date(2026, 10, 22)));
+        assert_eq!(parse("1个月后"), Some(date(2026, 11, 19)));
+        assert_eq!(parse("2周前"), Some(date(2026, 10, 5)));
+        assert_eq!(parse("月底"), Some(date(2026, 10, 31)));
+        // 英文关键字在中文下仍然可用
+        assert_eq!(parse("next monday"), Some(date(2026, 10, 26)));
+
+        assert_eq!(parse_relative_day_in("明天", monday, Language::English), None);
+        assert_eq!(parse_relative_day_in("明天", monday, Language::Japanese), None);
+    }
+
+    #[test]
+    fn japanese_keywords_only_in_japanese() {
+        let monday = date(2026, 10, 19);
+        let parse = |text| parse_relative_day_in(text, monday, Language::Japanese);
+        assert_eq!(parse("明日"), Some(date(2026, 10, 20)));
+        assert_eq!(parse("来週"), Some(date(2026, 10, 26)));
+        assert_eq!(parse("来週金曜日"), Some(date(2026, 10, 30)));
+        assert_eq!(parse("金曜"), Some(date(2026, 10, 23)));
+        assert_eq!(parse("3日後"), Some(date(2026, 10, 22)));
+        assert_eq!(parse("2週間前"), Some(date(2026, 10, 5)));
+        assert_eq!(parse("1ヶ月後"), Some(date(2026, 11, 19)));
+
+        assert_eq!(parse_relative_day_in("来週", monday, Language::English), None);
+        assert_eq!(parse_relative_day_in("来週", monday, Language::Chinese), None);
+    }
+
+    // 过滤表达式
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let parsed = filter("a and b or not c").unwrap();
+        match parsed.conditions.as_slice() {
+            [Condition::Or(branches)] => {
+                assert!(matches!(branches.as_slice(), [Condition::And(both), Condition::Not(_)] if both.len() == 2));
+            },
+            other => panic!("unexpected parse: {:?}", other),
+        }
+
+        assert!(parsed.matches(&task("a b")));
+        assert!(parsed.matches(&task("x")));
+        assert!(!parsed.matches(&task("a c")));
+        assert!(parsed.matches(&task("a b c")));
+    }
+
+    #[test]
+    fn adjacent_conditions_are_and() {
+        let parsed = filter("report +work").unwrap();
+        let mut work = task("Write report");
+        assert!(!parsed.matches(&work));
+        work.tags.push("work".to_string());
+        assert!(parsed.matches(&work));
+    }
+
+    #[test]
+    fn parentheses_group_conditions() {
+        let grouped = filter("a and (b or c)").unwrap();
+        assert!(grouped.matches(&task("a c")));
+        assert!(!grouped.matches(&task("c")));
+
+        let negated = filter("not (a or b)").unwrap();
+        assert!(negated.matches(&task("c")));
+        assert!(!negated.matches(&task("b")));
+
+        let nested = filter("((a))").unwrap();
+        assert!(nested.matches(&task("a")));
+    }
+
+    #[test]
+    fn due_before_weekday() {
+        let today = Local::now().date_naive();
+        let friday = weekday_on_or_after(today, Weekday::Fri);
+        let parsed = filter("due:<fri").unwrap();
+        assert!(matches!(parsed.conditions.as_slice(), [Condition::Due(Comparison::Before, value)] if value.date_naive() == friday));
+
+        let due_on = |day: NaiveDate| {
+            let mut task = task("x");
+            task.due_date = local_datetime(day, NaiveTime::MIN);
+            task
+        };
+        assert!(parsed.matches(&due_on(friday - chrono::Duration::days(1))));
+        assert!(!parsed.matches(&due_on(friday)));
+        assert!(!parsed.matches(&task("no due date")));
+        assert!(filter("due:<=fri").unwrap().matches(&due_on(friday)));
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        let t = get_translations();
+        assert_eq!(filter("(a or b").unwrap_err(), t.incomplete_filter());
+        assert_eq!(filter("a and").unwrap_err(), t.incomplete_filter());
+        assert_eq!(filter("not").unwrap_err(), t.incomplete_filter());
+        assert_eq!(filter("a )").unwrap_err(), t.invalid_filter(")"));
+        assert_eq!(filter("due:<someday").unwrap_err(), t.invalid_filter("due:<someday"));
+        assert_eq!(filter("color:red").unwrap_err(), t.invalid_filter("color:red"));
+        assert_eq!(filter("priority:urgent").unwrap_err(), t.invalid_filter("priority:urgent"));
+        assert_eq!(filter("--bogus").unwrap_err(), t.option_in_filter("--bogus"));
+        assert_eq!(filter("+work --sort").unwrap_err(), t.option_in_filter("--sort"));
+        assert_eq!(filter("+").unwrap_err(), t.invalid_tag("+"));
+        assert_eq!(TaskFilter::from_args(&["--due-before".to_string()]).unwrap_err(), t.incomplete_filter());
+    }
+
+    // 重复规则
+
+    #[test]
+    fn parse_repeat_rules() {
+        assert!(matches!(parse_repeat("daily"), Ok(Some(Repeat::Daily))));
+        assert!(matches!(parse_repeat("none"), Ok(None)));
+        assert!(matches!(parse_repeat("monthly:31"), Ok(Some(Repeat::Monthly { day: 31 }))));
+        assert!(matches!(parse_repeat("every:3d"), Ok(Some(Repeat::Every { days: 3 }))));
+        assert!(matches!(parse_repeat("after:2w"), Ok(Some(Repeat::After { days: 14 }))));
+        match parse_repeat("Weekly: mon,thu") {
+            Ok(Some(Repeat::Weekly { weekdays })) => assert_eq!(weekdays, vec![Weekday::Mon, Weekday::Thu]),
+            other => panic!("unexpected rule: {:?}", other),
+        }
+
+        for invalid in ["monthly:32", "monthly:0", "weekly:someday", "every:0d", "after:12h", "every", "hourly"] {
+            assert!(parse_repeat(invalid).is_err(), "{} should be rejected", invalid);
+        }
+        assert!(parse_repeat(&format!("every:{}d", MAX_REPEAT_DAYS + 1)).is_err());
+    }
+
+    #[test]
+    fn monthly_clamps_to_month_end() {
+        let rule = Repeat::Monthly { day: 31 };
+        assert_eq!(rule.next_date(date(2026, 1, 31), date(2026, 1, 31)), date(2026, 2, 28));
+        assert_eq!(rule.next_date(date(2026, 2, 28), date(2026, 2, 28)), date(2026, 3, 31));
+        assert_eq!(rule.next_date(date(2027, 12, 31), date(2027, 12, 31)), date(2028, 1, 31));
+        assert_eq!(rule.next_date(date(2028, 1, 31), date(2028, 1, 31)), date(2028, 2, 29));
+    }
+
+    #[test]
+    fn weekly_wraps_to_next_week() {
+        let rule = Repeat::Weekly { weekdays: vec![Weekday::Mon, Weekday::Thu] };
+        // 2026-10-19 是星期一
+        assert_eq!(rule.next_date(date(2026, 10, 19), date(2026, 10, 19)), date(2026, 10, 22));
+        assert_eq!(rule.next_date(date(2026, 10, 22), date(2026, 10, 22)), date(2026, 10, 26));
+        assert_eq!(rule.next_date(date(2026, 10, 24), date(2026, 10, 24)), date(2026, 10, 26));
+    }
+
+    #[test]
+    fn every_counts_from_due_and_after_from_completion() {
+        let due = date(2026, 10, 10);
+        let completed = date(2026, 10, 15);
+        assert_eq!(Repeat::Every { days: 3 }.next_date(due, completed), date(2026, 10, 13));
+        assert_eq!(Repeat::After { days: 3 }.next_date(due, completed), date(2026, 10, 18));
+    }
+
+    #[test]
+    fn late_completion_skips_past_occurrences() {
+        let today = date(2026, 10, 19);
+        assert_eq!(Repeat::Daily.next_date_after(date(2026, 10, 1), today), date(2026, 10, 20));
+        assert_eq!(Repeat::Daily.next_date_after(date(2026, 10, 19), today), date(2026, 10, 20));
+        assert_eq!(Repeat::Every { days: 7 }.next_date_after(date(2026, 10, 5), today), date(2026, 10, 26));
+        assert_eq!(Repeat::Monthly { day: 31 }.next_date_after(date(2026, 7, 31), today), date(2026, 10, 31));
+        let weekly = Repeat::Weekly { weekdays: vec![Weekday::Mon, Weekday::Thu] };
+        assert_eq!(weekly.next_date_after(date(2026, 9, 3), today), date(2026, 10, 22));
+        // 完成后N天的规则总是从今天算起
+        assert_eq!(Repeat::After { days: 2 }.next_date_after(date(2026, 9, 1), today), date(2026, 10, 21));
+        // 提前完成时只前进一次
+        assert_eq!(Repeat::Daily.next_date_after(date(2026, 10, 25), today), date(2026, 10, 26));
+    }
+
+    // 在编辑器中编辑的文档
+
+    fn editable_task() -> Task {
+        let mut task = task("Write report");
+        task.due_date = local_datetime(date(2026, 10, 20), NaiveTime::from_hms_opt(15, 30, 0).unwrap());
+        task.set_priority(Priority::Medium);
+        task.repeat = Some(Repeat::Weekly { weekdays: vec![Weekday::Mon, Weekday::Thu] });
+        task.tags = vec!["work".to_string(), "@office".to_string()];
+        task.notes = "first line\n\n  indented\nhttps://example.com/a:b".to_string();
+        task
+    }
+
+    #[test]
+    fn unchanged_document_round_trips() {
+        let first = editable_task();
+        let mut second = task("Buy milk");
+        second.id = 2;
+        second.completed = true;
+        let originals = vec![&first, &second];
+
+        let document = tasks_to_document(&originals);
+        let edits = parse_document(&document, &originals).unwrap();
+        assert!(edits.is_empty(), "unexpected edits: {:?}", edits);
+    }
+
+    #[test]
+    fn changed_fields_become_edits() {
+        let original = editable_task();
+        let originals = vec![&original];
+        let document = tasks_to_document(&originals)
+            .replace("description: Write report", "description: Write the report")
+            .replace("priority: medium", "priority: h")
+            .replace("repeat: weekly:mon,thu", "repeat:")
+            .replace("tags: work @office", "tags: +work, home")
+            .replace("completed: no", "completed: yes")
+            .replace("  indented\n", "  indented\n  added\n");
+
+        let edits = parse_document(&document, &originals).unwrap();
+        assert_eq!(edits.len(), 1);
+        let edit = &edits[0];
+        assert_eq!(edit.uid, original.uid);
+        assert_eq!(edit.description.as_deref(), Some("Write the report"));
+        assert!(edit.due_date.is_none());
+        assert_eq!(edit.priority, Some(Priority::High));
+        assert!(matches!(edit.repeat, Some(None)));
+        assert_eq!(edit.tags, Some(vec!["work".to_string(), "home".to_string()]));
+        assert_eq!(edit.completed, Some(true));
+        assert_eq!(edit.notes.as_deref(), Some("first line\n\n  indented\nadded\nhttps://example.com/a:b"));
+    }
+
+    #[test]
+    fn clearing_the_due_date() {
+        let original = editable_task();
+        let originals = vec![&original];
+        let document = tasks_to_document(&originals).replace("due: 2026-10-20 15:30", "due: none");
+        let edits = parse_document(&document, &originals).unwrap();
+        assert!(matches!(edits.as_slice(), [TaskEdit { due_date: Some(None), .. }]));
+    }
+
+    #[test]
+    fn malformed_documents_report_each_line() {
+        let t = get_translations();
+        let original = editable_task();
+        let originals = vec![&original];
+        let header = format!("[1 @{}]", original.uid);
+        let document = [
+            "description: before any task",
+            header.as_str(),
+            "description:",
+            "due: someday",
+            "priority: urgent",
+            "completed: maybe",
+            "colour: red",
+            "just some text",
+            header.as_str(),
+            "[9 @00000000-0000-0000-0000-000000000000]",
+        ].join("\n");
+
+        let errors = parse_document(&document, &originals).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|(line_no, _)| *line_no).collect();
+        assert_eq!(lines, vec![1, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(errors[0].1, t.field_outside_task("description"));
+        assert_eq!(errors[1].1, format!("description: {}", t.empty_description()));
+        assert_eq!(errors[4].1, format!("completed: {}", t.invalid_completed("maybe")));
+        assert_eq!(errors[5].1, t.unknown_field("colour"));
+        assert_eq!(errors[6].1, t.invalid_document_line("just some text"));
+        assert_eq!(errors[7].1, t.duplicate_task_section(&header[1..header.len() - 1]));
+        assert_eq!(errors[8].1, t.unknown_task_section("9 @00000000-0000-0000-0000-000000000000"));
+    }
+
+    #[test]
+    fn reopened_document_points_at_the_failing_lines() {
+        let original = editable_task();
+        let originals = vec![&original];
+        let content = tasks_to_document(&originals)
+            .replace("priority: medium", "priority: urgent")
+            .replace("completed: no", "completed: maybe");
+        let errors = parse_document(&content, &originals).unwrap_err();
+        assert_eq!(errors.len(), 2);
+
+        // 与 edit_tasks_in_editor 相同：错误提示写在顶部，行号对应重新打开后的文档
+        let messages = document_error_messages(&errors);
+        let reopened = messages.iter()
+            .map(|message| format!("{} {}\n", DOCUMENT_ERROR_PREFIX, message))
+            .collect::<String>() + &content;
+        let t = get_translations();
+        let reopened_lines: Vec<&str> = reopened.lines().collect();
+        for ((line_no, message), shown) in errors.iter().zip(&messages) {
+            let reported = line_no + errors.len();
+            assert_eq!(shown, &t.document_error(reported, message));
+            assert_eq!(reopened_lines[reported - 1], content.lines().nth(line_no - 1).unwrap());
+        }
+
+        // 再次解析前去掉错误提示，得到与上次相同的内容
+        assert_eq!(strip_document_errors(&reopened), content);
+        let fixed = strip_document_errors(&reopened)
+            .replace("priority: urgent", "priority: low")
+            .replace("completed: maybe", "completed: no");
+        let edits = parse_document(&fixed, &originals).unwrap();
+        assert!(matches!(edits.as_slice(), [TaskEdit { priority: Some(Priority::Low), completed: None, .. }]));
+    }
+
+    // todo.txt 导出
+
+    fn uid(n: u8) -> Uuid {
+        Uuid::from_bytes([n; 16])
+    }
+
+    fn dated_task(description: &str) -> Task {
+        let mut task = task(description);
+        task.uid = uid(1);
+        task.created_at = local_datetime(date(2026, 10, 18), NaiveTime::from_hms_opt(9, 0, 0).unwrap()).unwrap();
+        task
+    }
+
+    #[test]
+    fn todotxt_priority_tags_and_due() {
+        let mut task = dated_task("Call mom");
+        task.set_priority(Priority::High);
+        task.tags = vec!["@phone".to_string(), "family".to_string()];
+        task.due_date = local_datetime(date(2026, 10, 20), NaiveTime::from_hms_opt(15, 0, 0).unwrap());
+        assert_eq!(
+            todotxt_line(&task),
+            "(A) 2026-10-18 Call mom @phone +family due:2026-10-20 due-time:15:00 uid:01010101-0101-0101-0101-010101010101"
+        );
+
+        task.set_priority(Priority::Low);
+        task.due_date = local_datetime(date(2026, 10, 20), NaiveTime::MIN);
+        task.tags.clear();
+        assert_eq!(todotxt_line(&task), "(C) 2026-10-18 Call mom due:2026-10-20 uid:01010101-0101-0101-0101-010101010101");
+    }
+
+    #[test]
+    fn todotxt_completed_tasks() {
+        let mut task = dated_task("Book flights");
+        task.set_priority(Priority::Medium);
+        task.completed = true;
+        task.completed_at = local_datetime(date(2026, 10, 19), NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+        assert_eq!(todotxt_line(&task), "x 2026-10-19 2026-10-18 Book flights uid:01010101-0101-0101-0101-010101010101 pri:B");
+
+        // 旧版本完成的任务以创建日期作为完成日期
+        task.completed_at = None;
+        task.set_priority(Priority::None);
+        assert_eq!(todotxt_line(&task), "x 2026-10-18 2026-10-18 Book flights uid:01010101-0101-0101-0101-010101010101");
+    }
+
+    #[test]
+    fn todotxt_repeat_parent_and_dependencies() {
+        let mut task = dated_task("Standup");
+        task.due_date = local_datetime(date(2026, 10, 19), NaiveTime::MIN);
+        task.repeat = Some(Repeat::Weekly { weekdays: vec![Weekday::Mon] });
+        task.parent = Some(uid(2));
+        task.depends_on = vec![uid(3), uid(4)];
+        task.notes = "not exported".to_string();
+        assert_eq!(
+            todotxt_line(&task),
+            "2026-10-18 Standup due:2026-10-19 rec:+1w uid:01010101-0101-0101-0101-010101010101 \
+             parent:02020202-0202-0202-0202-020202020202 \
+             dep:03030303-0303-0303-0303-030303030303 dep:04040404-0404-0404-0404-040404040404"
+        );
+    }
+
+    #[test]
+    fn todotxt_rec_values() {
+        let monday = Some(date(2026, 10, 19));
+        assert_eq!(todotxt_rec(&Repeat::Daily, monday), "+1d");
+        assert_eq!(todotxt_rec(&Repeat::Every { days: 3 }, monday), "+3d");
+        assert_eq!(todotxt_rec(&Repeat::After { days: 3 }, monday), "3d");
+        assert_eq!(todotxt_rec(&Repeat::Weekly { weekdays: vec![Weekday::Mon] }, monday), "+1w");
+        assert_eq!(todotxt_rec(&Repeat::Weekly { weekdays: vec![Weekday::Tue] }, monday), "weekly:tue");
+        assert_eq!(todotxt_rec(&Repeat::Weekly { weekdays: vec![Weekday::Mon, Weekday::Thu] }, monday), "weekly:mon,thu");
+        assert_eq!(todotxt_rec(&Repeat::Monthly { day: 19 }, monday), "+1m");
+        assert_eq!(todotxt_rec(&Repeat::Monthly { day: 31 }, monday), "monthly:31");
+        assert_eq!(todotxt_rec(&Repeat::Monthly { day: 19 }, None), "monthly:19");
+
+        // 无法用 todo.txt 惯例表示的规则可以按 --repeat 的语法读回
+        assert!(matches!(parse_repeat("weekly:mon,thu"), Ok(Some(Repeat::Weekly { weekdays })) if weekdays == [Weekday::Mon, Weekday::Thu]));
+    }
+
+    #[test]
+    fn todotxt_escapes_syntax_in_descriptions() {
+        let task = dated_task("Pay back @anna +5 before due:friday at 10:30, see https://example.com \\odd + @ a:");
+        assert_eq!(
+            todotxt_line(&task),
+            "2026-10-18 Pay back \\@anna \\+5 before \\due:friday at \\10:30, see https://example.com \\\\odd + @ a: \
+             uid:01010101-0101-0101-0101-010101010101"
+        );
+    }
+
+    #[test]
+    fn todotxt_escaping_is_reversible() {
+        let unescape = |word: &str| word.strip_prefix('\\').unwrap_or(word).to_string();
+        for description in ["+work @home due:fri", "\\+work \\\\x", "plain words", "a:b https://x.org/p?q=1 10:30"] {
+            let escaped: Vec<String> = description.split(' ').map(todotxt_escape).collect();
+            assert!(escaped.iter().all(|word| !word.starts_with(['+', '@']) || word.len() == 1));
+            let restored: Vec<String> = escaped.iter().map(|word| unescape(word)).collect();
+            assert_eq!(restored.join(" "), description);
+        }
+    }
+
+    // 内联标签
+
+    #[test]
+    fn inline_tags_need_a_letter() {
+        assert_eq!(extract_tags("bump to +1"), ("bump to +1".to_string(), vec![]));
+        assert_eq!(extract_tags("call +@phone +mom"), ("call".to_string(), vec!["@phone".to_string(), "mom".to_string()]));
+        assert_eq!(extract_tags("+Work report +work"), ("report".to_string(), vec!["work".to_string()]));
+        assert_eq!(extract_tags("写报告 +工作"), ("写报告".to_string(), vec!["工作".to_string()]));
+        assert_eq!(extract_tags("price +5% and +@1"), ("price +5% and +@1".to_string(), vec![]));
+        assert_eq!(extract_tags("+only +tags"), ("+only +tags".to_string(), vec![]));
+    }
+
+    #[test]
+    fn inline_tags_keep_the_remaining_text() {
+        assert_eq!(extract_tags("a  b +x   c").0, "a  b   c");
+        assert_eq!(extract_tags("+x first\nsecond +y").0, "first\nsecond");
+        assert_eq!(extract_tags("  indented text ").0, "  indented text ");
+        assert_eq!(extract_tags("text +x ").0, "text");
+    }
+}